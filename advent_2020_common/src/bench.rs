@@ -0,0 +1,155 @@
+use super::{Error, Solution};
+use serde::Serialize;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Wall time spread over every run of one step, in nanoseconds
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Timings {
+    pub min_ns: u128,
+    pub median_ns: u128,
+    pub max_ns: u128,
+}
+
+impl Timings {
+    fn from_samples(samples: &mut [Duration]) -> Timings {
+        samples.sort();
+        Timings{
+            min_ns: samples.first().map(|d| d.as_nanos()).unwrap_or(0),
+            median_ns: samples.get(samples.len() / 2).map(|d| d.as_nanos()).unwrap_or(0),
+            max_ns: samples.last().map(|d| d.as_nanos()).unwrap_or(0),
+        }
+    }
+}
+
+impl std::fmt::Display for Timings {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "min {:?} / median {:?} / max {:?}",
+            Duration::from_nanos(self.min_ns as u64),
+            Duration::from_nanos(self.median_ns as u64),
+            Duration::from_nanos(self.max_ns as u64),
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct BenchReport {
+    pub day: usize,
+    pub iterations: usize,
+    pub parse: Timings,
+    pub part1: Timings,
+    pub part2: Timings,
+}
+
+impl BenchReport {
+    pub fn to_json(&self) -> String {
+        // only plain numbers in here, so serializing can't fail
+        serde_json::to_string(self).unwrap()
+    }
+}
+
+impl std::fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "Day {} ({} iterations)", self.day, self.iterations)?;
+        writeln!(f, "  parse:  {}", self.parse)?;
+        writeln!(f, "  part 1: {}", self.part1)?;
+        write!(f, "  part 2: {}", self.part2)
+    }
+}
+
+/// Run a day's parse step, part 1 and part 2 `iterations` times each and collect the timings.
+/// Each part is timed against the same parsed input.  Inputs and results go through
+/// `black_box`, so the optimiser can't skip work whose result is never used.
+/// Examples:
+/// ```
+/// use advent_2020_common::{bench::bench, Error, Solution};
+///
+/// struct Count;
+///
+/// impl Solution for Count {
+///     type Input = Vec<String>;
+///     type First = usize;
+///     type Second = usize;
+///
+///     fn parse(&self, input: &str) -> Result<Vec<String>, Error> { Ok(advent_2020_common::to_lines(input)) }
+///     fn part1(&self, input: &Vec<String>) -> Result<usize, Error> { Ok(input.len()) }
+///     fn part2(&self, input: &Vec<String>) -> Result<usize, Error> { Ok(input.len() * 2) }
+/// }
+///
+/// let report = bench(1, &Count, "a\nb", 5).unwrap();
+/// assert_eq!(report.iterations, 5);
+/// assert!(report.parse.min_ns <= report.parse.median_ns);
+/// assert!(report.parse.median_ns <= report.parse.max_ns);
+/// ```
+///
+pub fn bench<S: Solution>(day: usize, solution: &S, input: &str, iterations: usize) -> Result<BenchReport, Error> {
+    if iterations == 0 {
+        return Err(Error::invalid_input("need at least one iteration to benchmark"));
+    }
+
+    let mut parse_samples = Vec::with_capacity(iterations);
+    let mut parsed = None;
+    for _ in 0 .. iterations {
+        let start = Instant::now();
+        let result = black_box(solution.parse(black_box(input))?);
+        parse_samples.push(start.elapsed());
+        parsed = Some(result);
+    }
+    let parsed = parsed.unwrap();
+
+    let mut part1_samples = Vec::with_capacity(iterations);
+    for _ in 0 .. iterations {
+        let start = Instant::now();
+        black_box(solution.part1(black_box(&parsed))?);
+        part1_samples.push(start.elapsed());
+    }
+
+    let mut part2_samples = Vec::with_capacity(iterations);
+    for _ in 0 .. iterations {
+        let start = Instant::now();
+        black_box(solution.part2(black_box(&parsed))?);
+        part2_samples.push(start.elapsed());
+    }
+
+    Ok(BenchReport{
+        day,
+        iterations,
+        parse: Timings::from_samples(&mut parse_samples),
+        part1: Timings::from_samples(&mut part1_samples),
+        part2: Timings::from_samples(&mut part2_samples),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timings_from_samples() {
+        let mut samples = vec!(
+            Duration::from_nanos(30),
+            Duration::from_nanos(10),
+            Duration::from_nanos(50),
+            Duration::from_nanos(20),
+            Duration::from_nanos(40),
+        );
+        let result = Timings::from_samples(&mut samples);
+        assert_eq!(result, Timings{min_ns: 10, median_ns: 30, max_ns: 50});
+    }
+
+    #[test]
+    fn test_to_json() {
+        let report = BenchReport{
+            day: 15,
+            iterations: 3,
+            parse: Timings{min_ns: 1, median_ns: 2, max_ns: 3},
+            part1: Timings{min_ns: 4, median_ns: 5, max_ns: 6},
+            part2: Timings{min_ns: 7, median_ns: 8, max_ns: 9},
+        };
+        let expected = "{\"day\":15,\"iterations\":3,\
+            \"parse\":{\"min_ns\":1,\"median_ns\":2,\"max_ns\":3},\
+            \"part1\":{\"min_ns\":4,\"median_ns\":5,\"max_ns\":6},\
+            \"part2\":{\"min_ns\":7,\"median_ns\":8,\"max_ns\":9}}";
+        assert_eq!(report.to_json(), expected);
+    }
+}
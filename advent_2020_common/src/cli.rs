@@ -0,0 +1,187 @@
+use super::{bench::bench, loader::read_source, report::run, to_lines, verify::{verify, Answers}, Context, Error, Solution};
+
+pub const DEFAULT_BENCH_ITERATIONS: usize = 10;
+
+/// Where a day's known answers for its input.txt are checked in, relative to the day's crate
+pub const ANSWERS_FILE: &str = "answers.txt";
+
+const USAGE: &str = "usage: [input file | --input path] [--bench [iterations]] [--json] [--verify]";
+
+/// Command line options shared by every day's binary
+#[derive(Debug, PartialEq)]
+pub struct DayArgs {
+    pub input: String,
+    pub bench_iterations: Option<usize>,
+    pub json: bool,
+    pub verify: bool,
+}
+
+impl DayArgs {
+    /// Parse a day binary's command line (not including the program name).
+    /// The input file is the first argument or given with `--input`, defaults to 'input.txt',
+    /// and '-' means stdin.
+    /// Examples:
+    /// ```
+    /// use advent_2020_common::cli::DayArgs;
+    /// let args: Vec<String> = vec!("my_input.txt", "--bench", "5").iter().map(|s| String::from(*s)).collect();
+    /// let parsed = DayArgs::parse(&args).unwrap();
+    /// assert_eq!(parsed, DayArgs{input: String::from("my_input.txt"), bench_iterations: Some(5), json: false, verify: false});
+    /// ```
+    ///
+    pub fn parse(args: &[String]) -> Result<DayArgs, Error> {
+        let mut input = None;
+        let mut bench_iterations = None;
+        let mut json = false;
+        let mut verify = false;
+
+        let mut args = args.iter().peekable();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--bench" => {
+                    let iterations = match args.peek().map(|s| s.parse::<usize>()) {
+                        Some(Ok(iterations)) => {
+                            args.next();
+                            iterations
+                        },
+                        _ => DEFAULT_BENCH_ITERATIONS,
+                    };
+                    bench_iterations = Some(iterations);
+                },
+                x if x == "--input" || x.starts_with("--input=") => {
+                    if input.is_some() {
+                        return Err(Error::invalid_input(format!("input given more than once\n{}", USAGE)));
+                    }
+                    let path = match x.strip_prefix("--input=") {
+                        Some(path) => String::from(path),
+                        None => args.next().cloned().ok_or_else(|| Error::invalid_input(format!("missing value for --input\n{}", USAGE)))?,
+                    };
+                    input = Some(path);
+                },
+                "--json" => json = true,
+                "--verify" => verify = true,
+                x if x.starts_with("--") => return Err(Error::invalid_input(format!("unknown option {}\n{}", x, USAGE))),
+                x if input.is_none() => input = Some(String::from(x)),
+                x => return Err(Error::invalid_input(format!("unexpected argument {}\n{}", x, USAGE))),
+            }
+        }
+
+        if json && verify {
+            return Err(Error::invalid_input(format!("--json can't be combined with --verify\n{}", USAGE)));
+        }
+        if verify && bench_iterations.is_some() {
+            return Err(Error::invalid_input(format!("--verify can't be combined with --bench\n{}", USAGE)));
+        }
+
+        Ok(DayArgs{
+            input: input.unwrap_or_else(|| String::from("input.txt")),
+            bench_iterations,
+            json,
+            verify,
+        })
+    }
+}
+
+/// Everything a day's `main` needs to do: read the input, then either solve
+/// and print both parts, benchmark them when run with `--bench`, or check them
+/// against the day's answers.txt when run with `--verify`.  With `--json` the
+/// parts are printed as one JSON object per line, see `report::PartResult`.
+pub fn day_main<S: Solution>(day: usize, solution: &S) -> Result<(), Error> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args = DayArgs::parse(&args)?;
+    let input = read_source(&args.input).with_context(|| format!("Couldn't read input file {}", args.input))?;
+
+    if let Some(iterations) = args.bench_iterations {
+        let report = bench(day, solution, &input, iterations)?;
+        if args.json {
+            println!("{}", report.to_json());
+        } else {
+            println!("{}", report);
+        }
+        return Ok(());
+    }
+
+    if args.verify {
+        let answers = Answers::from_file(ANSWERS_FILE)?;
+        let results = verify(day, solution, &input, &answers);
+        for result in results.iter() {
+            println!("{}", result);
+        }
+        if results.iter().any(|r| r.failed()) {
+            return Err(Error::no_solution("verification failed"));
+        }
+        return Ok(());
+    }
+
+    if args.json {
+        let results = run(day, solution, &input, &[1, 2]);
+        for result in results.iter() {
+            println!("{}", result.to_json());
+        }
+        if results.iter().any(|r| r.failed()) {
+            return Err(Error::no_solution("solving failed"));
+        }
+        return Ok(());
+    }
+
+    println!("Using puzzle input {:?}", to_lines(&input));
+    let parsed = solution.parse(&input)?;
+    println!("First output: {}", solution.part1(&parsed)?);
+    println!("Second output: {}", solution.part2(&parsed)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(input: &[&str]) -> Vec<String> {
+        input.iter().map(|s| String::from(*s)).collect()
+    }
+
+    #[test]
+    fn test_parse_defaults() {
+        let result = DayArgs::parse(&args(&[])).unwrap();
+        assert_eq!(result, DayArgs{input: String::from("input.txt"), bench_iterations: None, json: false, verify: false});
+    }
+
+    #[test]
+    fn test_parse_bench() {
+        let result = DayArgs::parse(&args(&["--bench", "--json"])).unwrap();
+        assert_eq!(result, DayArgs{input: String::from("input.txt"), bench_iterations: Some(DEFAULT_BENCH_ITERATIONS), json: true, verify: false});
+
+        let result = DayArgs::parse(&args(&["--json", "--bench", "3", "other.txt"])).unwrap();
+        assert_eq!(result, DayArgs{input: String::from("other.txt"), bench_iterations: Some(3), json: true, verify: false});
+    }
+
+    #[test]
+    fn test_parse_input() {
+        let expected = DayArgs{input: String::from("other.txt"), bench_iterations: Some(2), json: false, verify: false};
+        assert_eq!(DayArgs::parse(&args(&["--bench", "2", "--input", "other.txt"])).unwrap(), expected);
+        assert_eq!(DayArgs::parse(&args(&["--input=other.txt", "--bench", "2"])).unwrap(), expected);
+
+        let result = DayArgs::parse(&args(&["-"])).unwrap();
+        assert_eq!(result.input, "-");
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let result = DayArgs::parse(&args(&["--json"])).unwrap();
+        assert_eq!(result, DayArgs{input: String::from("input.txt"), bench_iterations: None, json: true, verify: false});
+    }
+
+    #[test]
+    fn test_parse_verify() {
+        let result = DayArgs::parse(&args(&["--verify"])).unwrap();
+        assert_eq!(result, DayArgs{input: String::from("input.txt"), bench_iterations: None, json: false, verify: true});
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(DayArgs::parse(&args(&["--json", "--verify"])).is_err());
+        assert!(DayArgs::parse(&args(&["--verbose"])).is_err());
+        assert!(DayArgs::parse(&args(&["a.txt", "b.txt"])).is_err());
+        assert!(DayArgs::parse(&args(&["--verify", "--bench"])).is_err());
+        assert!(DayArgs::parse(&args(&["a.txt", "--input", "b.txt"])).is_err());
+        assert!(DayArgs::parse(&args(&["--input"])).is_err());
+    }
+}
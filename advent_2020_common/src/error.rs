@@ -0,0 +1,205 @@
+use std::fmt;
+
+/// Broad category of an `Error`, so callers can tell bad input apart from a puzzle with no answer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Reading input or other files failed
+    Io,
+    /// Input text couldn't be parsed, e.g. a token that's not a number
+    Parse,
+    /// Input parsed fine, but the puzzle has no answer for it
+    NoSolution,
+    /// Input or options parsed fine but don't make sense, e.g. an unknown instruction
+    InvalidInput,
+}
+
+/// Where in the input an error happened; line and column both count from 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub line: usize,
+    pub column: Option<usize>,
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.column {
+            Some(column) => write!(f, "line {}, column {}", self.line, column),
+            None => write!(f, "line {}", self.line),
+        }
+    }
+}
+
+type Source = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// Error for everything in the workspace: a kind, a message, optionally where in the input it
+/// happened, and optionally the error that caused it.  Displays as the whole chain of causes:
+/// ```
+/// use advent_2020_common::{Context, Error};
+///
+/// let result: Result<u32, Error> = "12a".parse::<u32>().at_line(3).context("reading bus ids");
+/// let error = result.unwrap_err();
+/// assert_eq!(error.to_string(), "reading bus ids (line 3): not an int: invalid digit found in string");
+/// ```
+///
+#[derive(Debug)]
+pub struct Error {
+    kind: ErrorKind,
+    msg: String,
+    span: Option<Span>,
+    source: Option<Source>,
+}
+
+impl Error {
+    pub fn new<S: Into<String>>(kind: ErrorKind, msg: S) -> Error {
+        Error{kind, msg: msg.into(), span: None, source: None}
+    }
+
+    pub fn io<S: Into<String>>(msg: S) -> Error {
+        Error::new(ErrorKind::Io, msg)
+    }
+
+    pub fn parse<S: Into<String>>(msg: S) -> Error {
+        Error::new(ErrorKind::Parse, msg)
+    }
+
+    pub fn no_solution<S: Into<String>>(msg: S) -> Error {
+        Error::new(ErrorKind::NoSolution, msg)
+    }
+
+    pub fn invalid_input<S: Into<String>>(msg: S) -> Error {
+        Error::new(ErrorKind::InvalidInput, msg)
+    }
+
+    /// Mark the error as happening at the given line (counting from 1)
+    pub fn at_line(mut self, line: usize) -> Error {
+        self.span = Some(Span{line, column: None});
+        self
+    }
+
+    /// Mark the error as happening at the given line and column (both counting from 1)
+    pub fn at(mut self, line: usize, column: usize) -> Error {
+        self.span = Some(Span{line, column: Some(column)});
+        self
+    }
+
+    /// Record the lower level error that caused this one
+    pub fn with_source<E: std::error::Error + Send + Sync + 'static>(mut self, source: E) -> Error {
+        self.source = Some(Box::new(source));
+        self
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    pub fn message(&self) -> &str {
+        &self.msg
+    }
+
+    pub fn span(&self) -> Option<Span> {
+        self.span
+    }
+
+    /// Wrap in a higher level description of what was being done; the kind and span carry over
+    fn wrap(mut self, context: String) -> Error {
+        let span = self.span.take();
+        Error{kind: self.kind, msg: context, span, source: Some(Box::new(self))}
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.msg)?;
+        if let Some(span) = self.span {
+            write!(f, " ({})", span)?;
+        }
+        if let Some(source) = &self.source {
+            write!(f, ": {}", source)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source.as_ref().map(|source| source.as_ref() as &(dyn std::error::Error + 'static))
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Error {
+        Error::io("I/O error").with_source(error)
+    }
+}
+
+impl From<std::num::ParseIntError> for Error {
+    fn from(error: std::num::ParseIntError) -> Error {
+        Error::parse("not an int").with_source(error)
+    }
+}
+
+/// Helpers for adding context to any result whose error converts into `Error`
+pub trait Context<T> {
+    /// Describe what was being done when the error happened
+    fn context<S: Into<String>>(self, context: S) -> Result<T, Error>;
+
+    /// Like `context`, but only builds the description if there was an error
+    fn with_context<S: Into<String>, F: FnOnce() -> S>(self, context: F) -> Result<T, Error>;
+
+    /// Mark the error as happening at the given line of the input (counting from 1)
+    fn at_line(self, line: usize) -> Result<T, Error>;
+}
+
+impl<T, E: Into<Error>> Context<T> for Result<T, E> {
+    fn context<S: Into<String>>(self, context: S) -> Result<T, Error> {
+        self.map_err(|e| e.into().wrap(context.into()))
+    }
+
+    fn with_context<S: Into<String>, F: FnOnce() -> S>(self, context: F) -> Result<T, Error> {
+        self.map_err(|e| e.into().wrap(context().into()))
+    }
+
+    fn at_line(self, line: usize) -> Result<T, Error> {
+        self.map_err(|e| e.into().at_line(line))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as StdError;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Error::parse("bad token").to_string(), "bad token");
+        assert_eq!(Error::parse("bad token").at(2, 7).to_string(), "bad token (line 2, column 7)");
+        assert_eq!(Error::no_solution("nothing sums to 2020").at_line(4).to_string(), "nothing sums to 2020 (line 4)");
+    }
+
+    #[test]
+    fn test_from_parse_int() {
+        let error: Error = "x".parse::<u32>().unwrap_err().into();
+        assert_eq!(error.kind(), ErrorKind::Parse);
+        assert_eq!(error.message(), "not an int");
+        assert!(error.source().is_some());
+    }
+
+    #[test]
+    fn test_from_io() {
+        let error: Error = std::fs::read_to_string("no/such/file").unwrap_err().into();
+        assert_eq!(error.kind(), ErrorKind::Io);
+        assert!(error.source().is_some());
+    }
+
+    #[test]
+    fn test_context() {
+        let result: Result<(), Error> = Err(Error::invalid_input("unknown op").at(5, 3));
+        let error = result.context("running program").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+        assert_eq!(error.span(), Some(Span{line: 5, column: Some(3)}));
+        assert_eq!(error.to_string(), "running program (line 5, column 3): unknown op");
+
+        let source = error.source().unwrap();
+        assert_eq!(source.to_string(), "unknown op");
+    }
+}
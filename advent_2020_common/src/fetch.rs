@@ -0,0 +1,184 @@
+use super::{Context, Error};
+use std::path::PathBuf;
+
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+
+/// Environment variable holding the value of the site's `session` cookie
+pub const SESSION_ENV: &str = "AOC_SESSION";
+/// Environment variable overriding `DEFAULT_BASE_URL`, e.g. to point at a local stand-in server
+pub const BASE_URL_ENV: &str = "AOC_BASE_URL";
+
+const USER_AGENT: &str = "advent_2020_rust input fetcher";
+
+/// Fetches puzzle inputs over HTTP and caches them on disk as `<cache dir>/<year>/day_<day>.txt`.
+/// Once an input is cached it's never fetched again, so the site only sees one request per day.
+/// Examples:
+/// ```no_run
+/// use advent_2020_common::fetch::InputProvider;
+/// let provider = InputProvider::new(".aoc_cache").with_session("53616c7465645f5f...");
+/// let input = provider.input(2020, 1).unwrap();
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct InputProvider {
+    base_url: String,
+    session: Option<String>,
+    cache_dir: PathBuf,
+}
+
+impl InputProvider {
+    pub fn new<P: Into<PathBuf>>(cache_dir: P) -> InputProvider {
+        InputProvider{
+            base_url: String::from(DEFAULT_BASE_URL),
+            session: None,
+            cache_dir: cache_dir.into(),
+        }
+    }
+
+    /// Like `new`, taking the session token and base url from `AOC_SESSION` / `AOC_BASE_URL` if set
+    pub fn from_env<P: Into<PathBuf>>(cache_dir: P) -> InputProvider {
+        let mut provider = InputProvider::new(cache_dir);
+        if let Ok(session) = std::env::var(SESSION_ENV) {
+            provider = provider.with_session(session.trim());
+        }
+        if let Ok(base_url) = std::env::var(BASE_URL_ENV) {
+            provider = provider.with_base_url(&base_url);
+        }
+        provider
+    }
+
+    pub fn with_base_url(mut self, base_url: &str) -> InputProvider {
+        self.base_url = String::from(base_url.trim_end_matches('/'));
+        self
+    }
+
+    pub fn with_session<S: Into<String>>(mut self, session: S) -> InputProvider {
+        self.session = Some(session.into());
+        self
+    }
+
+    pub fn cache_path(&self, year: u32, day: u32) -> PathBuf {
+        self.cache_dir.join(year.to_string()).join(format!("day_{}.txt", day))
+    }
+
+    pub fn is_cached(&self, year: u32, day: u32) -> bool {
+        self.cache_path(year, day).is_file()
+    }
+
+    /// The input for a day: read from the cache, or fetched and then cached if it isn't there yet
+    pub fn input(&self, year: u32, day: u32) -> Result<String, Error> {
+        let path = self.cache_path(year, day);
+        if path.is_file() {
+            return std::fs::read_to_string(&path).with_context(|| format!("Couldn't read cached input {}", path.display()));
+        }
+
+        let input = self.fetch(year, day)?;
+        self.store(&path, &input)?;
+        Ok(input)
+    }
+
+    fn fetch(&self, year: u32, day: u32) -> Result<String, Error> {
+        let session = self.session.as_ref()
+            .ok_or_else(|| Error::invalid_input(format!("no session token to fetch day {} with, set {}", day, SESSION_ENV)))?;
+        let url = format!("{}/{}/day/{}/input", self.base_url, year, day);
+
+        let response = ureq::get(&url)
+            .set("Cookie", &format!("session={}", session))
+            .set("User-Agent", USER_AGENT)
+            .call()
+            .map_err(|e| Error::io(format!("Couldn't fetch {}", url)).with_source(e))?;
+
+        response.into_string().with_context(|| format!("Couldn't read response from {}", url))
+    }
+
+    // write to a temporary file first, so a failed write can't leave a truncated input in the cache
+    fn store(&self, path: &std::path::Path, input: &str) -> Result<(), Error> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).with_context(|| format!("Couldn't create cache directory {}", parent.display()))?;
+        }
+        let partial_path = path.with_extension("partial");
+        std::fs::write(&partial_path, input).with_context(|| format!("Couldn't write {}", partial_path.display()))?;
+        std::fs::rename(&partial_path, path).with_context(|| format!("Couldn't write {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorKind;
+    use std::sync::{Arc, Mutex};
+    use std::thread::JoinHandle;
+
+    fn temp_cache_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("advent_2020_fetch_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    type Seen = Arc<Mutex<Vec<(String, String)>>>;
+
+    /// Serve `requests` requests with the given body, recording the url and cookie of each
+    fn serve(body: &'static str, requests: usize) -> (String, Seen, JoinHandle<()>) {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", server.server_addr());
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let seen_by_server = Arc::clone(&seen);
+
+        let handle = std::thread::spawn(move || {
+            for request in server.incoming_requests().take(requests) {
+                let cookie = request.headers().iter()
+                    .find(|h| h.field.equiv("Cookie"))
+                    .map(|h| h.value.to_string())
+                    .unwrap_or_default();
+                seen_by_server.lock().unwrap().push((request.url().to_string(), cookie));
+                request.respond(tiny_http::Response::from_string(body)).unwrap();
+            }
+        });
+
+        (base_url, seen, handle)
+    }
+
+    #[test]
+    fn test_fetch_then_cache() {
+        let (base_url, seen, handle) = serve("1721\n979\n", 1);
+        let cache_dir = temp_cache_dir("cache");
+        let provider = InputProvider::new(&cache_dir).with_base_url(&base_url).with_session("abc123");
+
+        assert!(!provider.is_cached(2020, 1));
+        assert_eq!(provider.input(2020, 1).unwrap(), "1721\n979\n");
+        handle.join().unwrap();
+        assert_eq!(*seen.lock().unwrap(), vec!((String::from("/2020/day/1/input"), String::from("session=abc123"))));
+
+        // the server has shut down, so this can only come from the cache
+        assert!(provider.is_cached(2020, 1));
+        assert_eq!(provider.input(2020, 1).unwrap(), "1721\n979\n");
+        assert_eq!(std::fs::read_to_string(cache_dir.join("2020").join("day_1.txt")).unwrap(), "1721\n979\n");
+
+        std::fs::remove_dir_all(&cache_dir).unwrap();
+    }
+
+    #[test]
+    fn test_fetch_without_session() {
+        let provider = InputProvider::new(temp_cache_dir("no_session")).with_base_url("http://127.0.0.1:1");
+        let result = provider.input(2020, 2).unwrap_err();
+        assert_eq!(result.kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_fetch_error_status() {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", server.server_addr());
+        let handle = std::thread::spawn(move || {
+            let request = server.recv().unwrap();
+            request.respond(tiny_http::Response::from_string("Please log in").with_status_code(400)).unwrap();
+        });
+
+        let cache_dir = temp_cache_dir("error_status");
+        let provider = InputProvider::new(&cache_dir).with_base_url(&base_url).with_session("expired");
+        let result = provider.input(2020, 3).unwrap_err();
+        handle.join().unwrap();
+
+        assert_eq!(result.kind(), ErrorKind::Io);
+        assert!(!provider.is_cached(2020, 3));
+    }
+}
@@ -1,60 +1,126 @@
-use std::fs::File;
-use std::io::prelude::*;
 use std::collections::HashSet;
 
+pub mod bench;
+pub mod cli;
+pub mod error;
+pub mod fetch;
 pub mod life;
+pub mod loader;
+pub mod records;
+pub mod report;
+pub mod solution;
+pub mod verify;
+
+pub use cli::day_main;
+pub use error::{Context, Error, ErrorKind};
+pub use loader::TrailingLine;
+pub use records::records;
+pub use solution::Solution;
 
 /// Read the puzzle input file; filename defaults to 'input.txt' 
-/// unless specified in the first command line argument
+/// unless given with `--input path` or as the first command line argument.
+/// '-' reads from stdin.  The empty line after a final newline is dropped.
 /// 
 pub fn input() -> Result<Vec<String>, std::io::Error> {
-    let mut args = std::env::args();
-    let input_filename = args.nth(1).unwrap_or(String::from("input.txt"));
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let input_filename = loader::input_path_from_args(&args).unwrap_or_else(|| String::from("input.txt"));
 
-    let mut file = File::open(input_filename)?;
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)?;
+    input_from_file(&input_filename, TrailingLine::Drop)
+}
 
-    let contents: Vec<String> = contents.split("\r\n")
-        .map(|s| String::from(s))
-        .collect();
+/// Read a puzzle input file at the given path (or stdin for '-'), split into lines
+///
+pub fn input_from_file(input_filename: &str, trailing: TrailingLine) -> Result<Vec<String>, std::io::Error> {
+    Ok(loader::split_lines(&loader::read_source(input_filename)?, trailing))
+}
 
-    Ok(contents)
+/// Split raw puzzle input into lines, accepting either LF or CRLF line endings
+/// Examples:
+/// ```
+/// let lines = advent_2020_common::to_lines("abc\r\ndef\nghi");
+/// assert_eq!(lines, vec!(String::from("abc"), String::from("def"), String::from("ghi")));
+/// ```
+/// 
+pub fn to_lines(input: &str) -> Vec<String> {
+    loader::split_lines(input, TrailingLine::Drop)
 }
 
-/// Convert an iterable of strings into u32s
+/// Parse one number per line, for any integer type.  Every line has to be a number;
+/// the error for one that isn't points at its line and column (both counting from 1).
 /// Examples:
 /// ```
 /// let inputs: Vec<String> = vec!(String::from("20"), String::from("50"));
-/// let nums = advent_2020_common::input_to_nums(&inputs);
-/// assert_eq!(nums, vec!(20, 50))
+/// let nums: Vec<u32> = advent_2020_common::input_to_nums(&inputs).unwrap();
+/// assert_eq!(nums, vec!(20, 50));
+///
+/// let inputs: Vec<String> = vec!(String::from("-20"), String::from(" 5x"));
+/// let error = advent_2020_common::input_to_nums::<i64>(&inputs).unwrap_err();
+/// assert_eq!(error.to_string(), "not a number (5x) (line 2, column 2): invalid digit found in string");
 /// ```
 /// 
-pub fn input_to_nums(input: &[String]) -> Vec<u32> {
+pub fn input_to_nums<T>(input: &[String]) -> Result<Vec<T>, Error> where
+    T: std::str::FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
     input
         .iter()
-        .map(|s| { s.parse()} )
-        .flatten()
+        .enumerate()
+        .map(|(i, line)| parse_token(line, i + 1, 1))
         .collect()
 }
 
-/// search for a vector of u32s looking for 2 numbers that sum to the sum_looked_for
+/// Parse a line of comma separated numbers, for any integer type.  `line_number` (counting from 1)
+/// is only used to say where a token that isn't a number is.
+/// Examples:
+/// ```
+/// let nums: Vec<i128> = advent_2020_common::csv_to_nums("0, 3,-6", 1).unwrap();
+/// assert_eq!(nums, vec!(0, 3, -6));
+///
+/// let error = advent_2020_common::csv_to_nums::<u64>("7,13,,59", 2).unwrap_err();
+/// assert_eq!(error.span().unwrap().to_string(), "line 2, column 6");
+/// ```
+/// 
+pub fn csv_to_nums<T>(line: &str, line_number: usize) -> Result<Vec<T>, Error> where
+    T: std::str::FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    let mut column = 1;
+    let mut nums = Vec::new();
+    for token in line.split(',') {
+        nums.push(parse_token(token, line_number, column)?);
+        column += token.len() + 1;
+    }
+    Ok(nums)
+}
+
+fn parse_token<T>(token: &str, line: usize, column: usize) -> Result<T, Error> where
+    T: std::str::FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    let trimmed = token.trim();
+    let column = column + token.len() - token.trim_start().len();
+    trimmed.parse().map_err(|e| Error::parse(format!("not a number ({})", trimmed)).with_source(e).at(line, column))
+}
+
+/// search for a vector of numbers looking for 2 numbers that sum to the sum_looked_for
 /// Some(v) if found, None otherwise.
 /// 
-pub fn find_complements(input: &[u32], sum_looked_for: u32, needs_multiply: bool) -> Option<u32> {
-    let mut seen_complements: HashSet<u32> = HashSet::new();  
+pub fn find_complements<T>(input: &[T], sum_looked_for: T, needs_multiply: bool) -> Option<T> where
+    T: Copy + Default + Eq + std::hash::Hash + PartialOrd + std::ops::Sub<Output = T> + std::ops::Mul<Output = T>,
+{
+    let mut seen_complements: HashSet<T> = HashSet::new();  
     for current in input.iter() {
         if sum_looked_for < *current {
             continue;
         }
 
-        let my_complement = sum_looked_for - current;
+        let my_complement = sum_looked_for - *current;
 
-        if seen_complements.contains(&current) {
+        if seen_complements.contains(current) {
             if needs_multiply {
-                return Some(current * my_complement);
+                return Some(*current * my_complement);
             } else {
-                return Some(0); // caller doesn't care about value of result
+                return Some(T::default()); // caller doesn't care about value of result
             }
         }
 
@@ -62,16 +128,3 @@ pub fn find_complements(input: &[u32], sum_looked_for: u32, needs_multiply: bool
     }
     None
 }
-
-#[derive(Debug)]
-pub struct Error{pub msg: String}
-
-impl Error {
-    pub fn new<T>(message: &str) -> std::result::Result<T, Error> {
-        Err(Error{msg: String::from(message)})
-    }
-    pub fn from_string<T>(message: String) -> std::result::Result<T, Error> {
-        Err(Error{msg:message})
-    }
-}
-
@@ -1,267 +1,412 @@
 use std::collections::{HashMap, VecDeque};
 use super::Error;
-use std::marker::PhantomData;
 
-#[derive(Debug, PartialEq, Hash, Clone, Copy, Eq)]
-pub enum TwoD {
-    X = 0, 
-    Y
+mod cycle;
+mod hashlife;
+mod hex;
+mod sight;
+mod image;
+mod pattern;
+mod snapshot;
+mod symmetry;
+mod step;
+mod storage;
+mod topology;
+
+pub use cycle::Evolution;
+pub use hashlife::HashLife;
+pub use hex::HexDirection;
+pub use sight::LineOfSight;
+pub use image::{Image, Palette, Recording, Rgb};
+pub use step::{LifeRule, NeighborCounts, Neighbors, Rule};
+pub use symmetry::Folded;
+pub use storage::{DenseGrid, Storage};
+pub use topology::{Boundary, Topology};
+
+/// What a cell in a `LifeSpace` can hold; anything small and comparable will do
+pub trait CellState: Copy + Eq + std::hash::Hash + std::fmt::Debug + Shared {}
+
+impl<T: Copy + Eq + std::hash::Hash + std::fmt::Debug + Shared> CellState for T {}
+
+/// With the `parallel` feature, cells, storage, neighbourhoods and rules are shared between
+/// the threads stepping a space, so must be `Send + Sync`; without it, anything goes
+#[cfg(feature = "parallel")]
+pub trait Shared: Send + Sync {}
+
+#[cfg(feature = "parallel")]
+impl<T: Send + Sync> Shared for T {}
+
+#[cfg(not(feature = "parallel"))]
+pub trait Shared {}
+
+#[cfg(not(feature = "parallel"))]
+impl<T> Shared for T {}
+
+/// Axis indexes into a `Coord`, so `coord.at(Y)` reads better than `coord.dim[1]`
+pub const X: usize = 0;
+pub const Y: usize = 1;
+pub const Z: usize = 2;
+pub const W: usize = 3;
+
+pub struct Assigner<const N: usize, T = LifeOption> {
+    pub spots: HashMap<Coord<N>, T>,
 }
 
-impl std::convert::TryFrom<usize> for TwoD {
-    type Error = Error;
+impl<const N: usize, T: CellState> Assigner<N, T> {
+    pub fn new() -> Self {
+        Assigner::<N, T>{spots: HashMap::new()}
+    }
 
-    fn try_from(value: usize) -> Result<Self, Self::Error> {
-        let ok_val = match value {
-            0 => TwoD::X,
-            1 => TwoD::Y,
-            _ => return Error::new("bad dim")
-        };
-        Ok(ok_val)
+    pub fn insert(&mut self, coord: Coord<N>, value: T) {
+        self.spots.insert(coord, value);
+    }
+
+    pub fn commit<S: Storage<N, T>>(&mut self, other: &mut LifeSpace<N, T, S>) {
+        for (coord, value) in self.spots.drain() {
+            other.spots.insert(coord, value);
+        }
+    }
+
+    pub fn empty(&self) -> bool {
+        self.spots.is_empty()
     }
 }
 
-impl std::convert::TryInto<usize> for TwoD {
-    type Error = Error;
+impl<const N: usize, T: CellState> Default for Assigner<N, T> {
+    fn default() -> Self {
+        Assigner::new()
+    }
+}
 
-    fn try_into(self) -> Result<usize, Self::Error> {
-        let ok_val = match self {
-            TwoD::X => 0,
-            TwoD::Y => 1,
-        };
-        Ok(ok_val)
+impl<T: CellState> Assigner<2, T> {
+    pub fn assign(&mut self, row: isize, column: isize, value: T) {
+        self.insert(Coord::new_2d(row, column), value);
     }
 }
 
+impl<T: CellState> Assigner<3, T> {
+    pub fn assign_3d(&mut self, row: isize, column: isize, z: isize, value: T) {
+        self.insert(Coord::new_3d(row, column, z), value);
+    }
+}
 
-#[derive(Debug, PartialEq, Hash, Clone, Copy, Eq)]
-pub enum ThreeD {
-    X = 0, 
-    Y,
-    Z
+#[derive(std::cmp::Eq, std::hash::Hash, Debug, PartialEq, Clone, Copy)]
+pub enum LifeOption {
+    Occupied,
+    Unoccupied,
+    Floor,
 }
 
-impl std::convert::TryFrom<usize> for ThreeD {
-    type Error = Error;
+/// Which character stands for which cell state, for reading a `LifeSpace` from
+/// text and writing it back out.
+/// Examples:
+/// ```
+/// use advent_2020_common::life::CharMap;
+///
+/// #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// enum Wire { Empty, Head, Tail, Conductor }
+///
+/// let map = CharMap::new(&[(' ', Wire::Empty), ('H', Wire::Head), ('t', Wire::Tail), ('#', Wire::Conductor)]);
+/// assert_eq!(map.state('H'), Some(Wire::Head));
+/// assert_eq!(map.char_for(Wire::Tail), Some('t'));
+/// assert_eq!(map.state('x'), None);
+/// ```
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct CharMap<T> {
+    pairs: Vec<(char, T)>,
+    missing: char,
+}
 
-    fn try_from(value: usize) -> Result<Self, Self::Error> {
-        let ok_val = match value {
-            0 => ThreeD::X,
-            1 => ThreeD::Y,
-            2 => ThreeD::Z,
-            _ => return Error::new("bad dim")
-        };
-        Ok(ok_val)
+impl<T: CellState> CharMap<T> {
+    /// If a state has more than one character, the first one is used when writing
+    pub fn new(pairs: &[(char, T)]) -> Self {
+        CharMap{pairs: pairs.to_vec(), missing: '.'}
     }
-}
 
-impl std::convert::TryInto<usize> for ThreeD {
-    type Error = Error;
+    /// The character written for cells that aren't in the space at all, '.' by default
+    pub fn with_missing(mut self, missing: char) -> Self {
+        self.missing = missing;
+        self
+    }
 
-    fn try_into(self) -> Result<usize, Self::Error> {
-        let ok_val = match self {
-            ThreeD::X => 0,
-            ThreeD::Y => 1,
-            ThreeD::Z => 2,
-        };
-        Ok(ok_val)
+    pub fn state(&self, c: char) -> Option<T> {
+        self.pairs.iter().find(|(pair_c, _)| *pair_c == c).map(|(_, state)| *state)
+    }
+
+    pub fn char_for(&self, state: T) -> Option<char> {
+        self.pairs.iter().find(|(_, pair_state)| *pair_state == state).map(|(c, _)| *c)
+    }
+
+    pub fn missing(&self) -> char {
+        self.missing
     }
 }
 
+impl CharMap<LifeOption> {
+    /// `#` occupied, `L` unoccupied and `.` floor, as in day 11's seat layouts
+    pub fn seats() -> Self {
+        CharMap::new(&[('#', LifeOption::Occupied), ('L', LifeOption::Unoccupied), ('.', LifeOption::Floor)])
+    }
+}
 
-#[derive(Debug, PartialEq, Hash, Clone, Copy, Eq)]
-pub enum FourD {
-    X = 0, 
-    Y,
-    Z,
-    T,
+/// A point in `N` dimensional space.  Stored inline, so coords are `Copy` and
+/// looking one up in a `LifeSpace` doesn't allocate.
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy)]
+pub struct Coord<const N: usize> {
+    pub dim: [isize; N],
 }
 
-impl std::convert::TryFrom<usize> for FourD {
-    type Error = Error;
+impl<const N: usize> Coord<N> {
+    pub fn new(dim: [isize; N]) -> Self {
+        Coord{dim}
+    }
 
-    fn try_from(value: usize) -> Result<Self, Self::Error> {
-        let ok_val = match value {
-            0 => FourD::X,
-            1 => FourD::Y,
-            2 => FourD::Z,
-            3 => FourD::T,
-            _ => return Error::new("bad dim")
-        };
-        Ok(ok_val)
+    /// A coord with the given row and column in its first two axes and 0 in the rest
+    pub fn from_2d(row: isize, column: isize) -> Self {
+        let mut dim = [0; N];
+        dim[X] = row;
+        dim[Y] = column;
+        Coord{dim}
     }
-}
 
-impl std::convert::TryInto<usize> for FourD {
-    type Error = Error;
+    pub fn at(&self, axis: usize) -> isize {
+        self.dim[axis]
+    }
 
-    fn try_into(self) -> Result<usize, Self::Error> {
-        let ok_val = match self {
-            FourD::X => 0,
-            FourD::Y => 1,
-            FourD::Z => 2,
-            FourD::T => 3,
-        };
-        Ok(ok_val)
+    pub fn set(&mut self, axis: usize, value: isize) {
+        self.dim[axis] = value;
     }
-}
 
-pub struct Assigner<DimensionType> where DimensionType : std::convert::TryInto<usize> {
-    pub spots: HashMap<Coord<DimensionType>, LifeOption>,
+    /// This coord moved by `diff` along every axis
+    pub fn offset(&self, diff: &[isize; N]) -> Self {
+        let mut dim = self.dim;
+        for (d, diff) in dim.iter_mut().zip(diff.iter()) {
+            *d += diff;
+        }
+        Coord{dim}
+    }
 }
 
-impl<DimensionType> Assigner<DimensionType> where 
-    DimensionType : std::convert::TryInto<usize>,
-    Coord<DimensionType> : Eq + std::hash::Hash,
-{
-    pub fn new() -> Self {
-        Assigner::<DimensionType>{spots: HashMap::new()}
+impl<const N: usize> From<[isize; N]> for Coord<N> {
+    fn from(dim: [isize; N]) -> Self {
+        Coord{dim}
     }
+}
 
-    pub fn assign(&mut self, row: isize, column: isize, value: LifeOption) {
-        self.spots.insert(Coord::<DimensionType>::new_2d(row, column), value);
+impl Coord<1> {
+    pub fn new_1d(v: isize) -> Self {
+        Coord{dim: [v]}
     }
+}
 
-    pub fn assign_3d(&mut self, row: isize, column: isize, z: isize, value: LifeOption) {
-        self.spots.insert(Coord::<DimensionType>::new_3d(row, column, z), value);
+impl Coord<2> {
+    pub fn new_2d(u: isize, v: isize) -> Self {
+        Coord{dim: [u, v]}
     }
-    
-    pub fn commit(&mut self, other: &mut LifeSpace<DimensionType>) {
-        for (coord, value) in self.spots.iter() {
-            other.spots.insert(coord.clone(), *value);
-        }
-        self.spots.clear();
+}
+
+impl Coord<3> {
+    pub fn new_3d(t: isize, u: isize, v: isize) -> Self {
+        Coord{dim: [t, u, v]}
     }
+}
 
-    pub fn empty(&self) -> bool {
-        self.spots.len() == 0
+impl Coord<4> {
+    pub fn new_4d(s: isize, t: isize, u: isize, v: isize) -> Self {
+        Coord{dim: [s, t, u, v]}
     }
 }
 
-#[derive(std::cmp::Eq, std::hash::Hash, Debug, PartialEq, Clone, Copy)]
-pub enum LifeOption {
-    Occupied,
-    Unoccupied,
-    Floor,
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum NeighborhoodKind {
+    /// Every cell within `radius` steps along each axis, diagonals included
+    Moore,
+    /// Every cell within `radius` steps in total, i.e. manhattan distance
+    VonNeumann,
+    /// Every cell within `radius` steps on a hex grid in axial coords, see `Coord::hex`;
+    /// only the first two axes move
+    Hex,
 }
 
-#[derive(std::cmp::Eq, Debug, PartialEq, Hash)]
-pub struct Coord<DimensionType> where DimensionType : std::convert::TryInto<usize> {
-    pub dim: Vec<isize>,
-    pub pd: PhantomData<DimensionType>,
+/// The offsets from a cell to each of its neighbours, worked out once up front so
+/// stepping a simulation is just adding offsets.
+/// Examples:
+/// ```
+/// use advent_2020_common::life::{Coord, Neighborhood};
+/// let moore = Neighborhood::<3>::moore(1);
+/// assert_eq!(moore.len(), 26);
+///
+/// let von_neumann = Neighborhood::<2>::von_neumann(1);
+/// let around: Vec<Coord<2>> = von_neumann.around(Coord::new_2d(5, 5)).collect();
+/// assert_eq!(around, vec!(Coord::new_2d(4, 5), Coord::new_2d(5, 4), Coord::new_2d(5, 6), Coord::new_2d(6, 5)));
+/// ```
+///
+#[derive(Debug, PartialEq, Clone)]
+pub struct Neighborhood<const N: usize> {
+    kind: NeighborhoodKind,
+    radius: usize,
+    offsets: Vec<[isize; N]>,
 }
 
-// impl<DimensionType, ValueType: Eq> Eq for Coord<DimensionType, ValueType> {
-//     fn eq(&self, other: &Coord<DimensionType, ValueType>) -> bool {
-//         self.dim == other.dim
-//     }
-// }
+impl<const N: usize> Neighborhood<N> {
+    /// Offsets are ordered with the first axis changing slowest, and never include the cell itself
+    pub fn new(kind: NeighborhoodKind, radius: usize) -> Self {
+        let side = 2 * radius + 1;
+        let r = radius as isize;
+        let mut offsets = Vec::new();
+
+        for i in 0 .. side.pow(N as u32) {
+            let mut offset = [0; N];
+            let mut rest = i;
+            for d in offset.iter_mut().rev() {
+                *d = (rest % side) as isize - r;
+                rest /= side;
+            }
 
-impl<DimensionType> Coord<DimensionType> where 
-    DimensionType: std::convert::TryInto<usize>
-{
-    pub fn new_1d(v: isize) -> Self {
-        Coord::<DimensionType>{dim: vec!(v), pd: PhantomData}
+            let in_range = match kind {
+                NeighborhoodKind::Moore => true,
+                NeighborhoodKind::VonNeumann => offset.iter().map(|d| d.abs()).sum::<isize>() <= r,
+                NeighborhoodKind::Hex => N >= 2 && offset.iter().skip(2).all(|d| *d == 0)
+                    && hex::hex_length(offset[X], offset[Y]) <= radius,
+            };
+            if in_range && offset.iter().any(|d| *d != 0) {
+                offsets.push(offset);
+            }
+        }
+
+        Neighborhood{kind, radius, offsets}
     }
 
-    pub fn new_2d(u: isize, v: isize) -> Self {
-        Coord::<DimensionType>{dim: vec!(u, v), pd: PhantomData}
+    pub fn moore(radius: usize) -> Self {
+        Neighborhood::new(NeighborhoodKind::Moore, radius)
     }
 
-    pub fn new_3d(t: isize, u: isize, v: isize) -> Self {
-        Coord::<DimensionType>{dim: vec!(t, u, v), pd: PhantomData}
+    pub fn von_neumann(radius: usize) -> Self {
+        Neighborhood::new(NeighborhoodKind::VonNeumann, radius)
     }
 
-    pub fn new_4d(s: isize, t: isize, u: isize, v: isize) -> Self {
-        Coord::<DimensionType>{dim: vec!(s, t, u, v), pd: PhantomData}
+    pub fn kind(&self) -> NeighborhoodKind {
+        self.kind
     }
 
-    pub fn at(&self, dimension: DimensionType) -> Result<isize, DimensionType::Error> {
-        Ok(self.dim[dimension.try_into()?])
+    pub fn radius(&self) -> usize {
+        self.radius
     }
 
-    pub fn set(&mut self, dimension: DimensionType, value: isize) -> Result<(), DimensionType::Error> {
-        Ok(self.dim[dimension.try_into()?] = value)
+    pub fn offsets(&self) -> &[[isize; N]] {
+        &self.offsets
     }
-}
 
-impl<DimensionType> Clone for Coord<DimensionType> where DimensionType: std::convert::TryInto<usize> {
-    fn clone(&self) -> Self {
-        let mut new_vec: Vec<isize> = Vec::new();
-        for i in self.dim.iter() {
-            new_vec.push(*i)
-        }
-        Coord::<DimensionType>{dim: new_vec, pd: PhantomData}
+    pub fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.offsets.is_empty()
+    }
+
+    /// The coords of every neighbour of `coord`
+    pub fn around(&self, coord: Coord<N>) -> impl Iterator<Item = Coord<N>> + '_ {
+        self.offsets.iter().map(move |offset| coord.offset(offset))
     }
 }
 
-pub struct LifeSpace<DimensionType> where DimensionType: std::convert::TryInto<usize> {
-    pub spots: HashMap<Coord<DimensionType>, LifeOption>,
-    pub tentative_spots: HashMap<Coord<DimensionType>, LifeOption>,
+/// The cells of an automaton in `N` dimensions, each in some state `T`, kept in
+/// whichever `Storage` suits it, in a space shaped by its `Topology`
+#[derive(Clone)]
+pub struct LifeSpace<const N: usize, T = LifeOption, S = HashMap<Coord<N>, T>> {
+    pub spots: S,
+    pub tentative_spots: HashMap<Coord<N>, T>,
+    /// Where the space's edges are, unbounded unless set with `with_topology`
+    pub topology: Topology<N>,
+    /// How many times the space has been stepped since it was parsed
+    pub generation: usize,
 }
 
-impl<DimensionType> LifeSpace<DimensionType> where 
-    DimensionType: std::convert::TryInto<usize>,
-    DimensionType: std::convert::TryFrom<usize>,
-    DimensionType: Copy,
-    DimensionType: std::fmt::Debug,
-    Coord<DimensionType> : Eq + std::hash::Hash,
-{
-    pub fn new(input: &[String], dimensions: usize) -> Result<Self, Error> {
-        let mut row = 0;
-        let mut result = LifeSpace::<DimensionType>{spots: HashMap::new(), tentative_spots: HashMap::new()};
-
-        for line in input.iter(){
-            let mut column = 0;
-            for spot in line.chars() {
-                let new_opt: LifeOption = match spot {
-                    '#' => LifeOption::Occupied,
-                    '.' => LifeOption::Floor,
-                    'L' => LifeOption::Unoccupied,
-                    x => return Error::from_string(format!("unknown char {}", x)),
-                };
-                let new_coord = match dimensions {
-                    2 => Coord::<DimensionType>::new_2d(row, column),
-                    3 => Coord::<DimensionType>::new_3d(row, column, 0),
-                    4 => Coord::<DimensionType>::new_4d(row, column, 0, 0),
-                    _ => return Error::new("unknown dimensionality requested"),
-                };
-
-                result.spots.insert(new_coord, new_opt);
-                column += 1;
+impl<const N: usize, S: Storage<N, LifeOption>> LifeSpace<N, LifeOption, S> {
+    /// Parse a 2d grid of `#`, `.` and `L` into the plane at 0 on every axis past the first two
+    pub fn new(input: &[String]) -> Result<Self, Error> {
+        LifeSpace::parse(input, &CharMap::seats())
+    }
+
+    pub fn to_string(&self) -> Result<String, Error> {
+        self.to_string_with(&CharMap::seats())
+    }
+}
+
+impl<const N: usize, T: CellState, S: Storage<N, T>> LifeSpace<N, T, S> {
+    /// Parse a 2d grid of characters, mapped to states by `chars`, into the plane at 0 on
+    /// every axis past the first two
+    pub fn parse(input: &[String], chars: &CharMap<T>) -> Result<Self, Error> {
+        if N < 2 {
+            return Err(Error::invalid_input("unknown dimensionality requested"));
+        }
+
+        let rows = input.len() as isize;
+        let columns = input.iter().map(|line| line.chars().count()).max().unwrap_or(0) as isize;
+        let spots = S::with_bounds(Coord::from_2d(0, 0), Coord::from_2d(rows - 1, columns - 1));
+        let mut result = LifeSpace::<N, T, S>{spots, tentative_spots: HashMap::new(), topology: Topology::unbounded(), generation: 0};
+
+        for (row, line) in input.iter().enumerate() {
+            for (column, spot) in line.chars().enumerate() {
+                let new_opt = chars.state(spot)
+                    .ok_or_else(|| Error::parse(format!("unknown char {}", spot)).at(row + 1, column + 1))?;
+                result.spots.insert(Coord::from_2d(row as isize, column as isize), new_opt);
             }
-            row += 1;
         }
         
         Ok(result)
     }
 
-    pub fn at_2d(&self, row: isize, column: isize) -> Option<&LifeOption> {
-        self.spots.get(&Coord::<DimensionType>::new_2d(row, column))
+    /// This space with its edges set by `topology`
+    /// Examples:
+    /// ```
+    /// use advent_2020_common::life::{Coord, LifeOption, LifeSpace, Neighborhood, Topology};
+    /// let input = vec!(String::from("#.."), String::from("..."), String::from("..L"));
+    /// let space = LifeSpace::<2>::new(&input).unwrap()
+    ///     .with_topology(Topology::torus(Coord::new_2d(0, 0), Coord::new_2d(2, 2)));
+    /// let neighbors: Vec<LifeOption> = space.neighbors_in(&Coord::new_2d(0, 0), &Neighborhood::moore(1)).collect();
+    /// assert!(neighbors.contains(&LifeOption::Unoccupied));
+    /// ```
+    ///
+    pub fn with_topology(mut self, topology: Topology<N>) -> Self {
+        self.topology = topology;
+        self
     }
 
-    pub fn at_3d(&self, row: isize, column: isize, z: isize) -> Option<&LifeOption> {
-        self.spots.get(&Coord::<DimensionType>::new_3d(row, column, z))
+    /// A space with no cells in it
+    pub fn empty() -> Self {
+        let spots = S::with_bounds(Coord::new([0; N]), Coord::new([-1; N]));
+        LifeSpace::<N, T, S>{spots, tentative_spots: HashMap::new(), topology: Topology::unbounded(), generation: 0}
     }
 
-    pub fn at_4d(&self, row: isize, column: isize, z: isize, t: isize) -> Option<&LifeOption> {
-        self.spots.get(&Coord::<DimensionType>::new_4d(row, column, z, t))
+    pub fn at(&self, coord: &Coord<N>) -> Option<&T> {
+        self.spots.get(coord)
     }
 
-    pub fn neighbors(&self, row: isize, column: isize) -> NeighborIterator<DimensionType> {
-        NeighborIterator::<DimensionType>{spots: &self.spots, row, column, count: 0, skip_chars: None, direction_count: 1}
+    /// How many cells are in the given state
+    pub fn count(&self, state: T) -> usize {
+        Storage::iter(&self.spots).filter(|(_, v)| **v == state).count()
     }
 
-    pub fn neighbors_skip_floor(&self, row: isize, column: isize) -> NeighborIterator<DimensionType> {
-        NeighborIterator::<DimensionType>{spots: &self.spots, row, column, count: 0, skip_chars: Some(LifeOption::Floor), direction_count: 1}
+    /// The states of the neighbours of `coord` that are in the space, found through its topology
+    pub fn neighbors_in<'a>(&'a self, coord: &Coord<N>, neighborhood: &'a Neighborhood<N>) -> impl Iterator<Item = T> + 'a {
+        neighborhood.around(*coord)
+            .filter_map(move |c| self.topology.resolve(&c))
+            .filter_map(move |c| self.spots.get(&c).copied())
     }
 
-    pub fn to_string(&self) -> Result<String, Error> {
+    /// Write the space out a 2d slice at a time, mapping states to characters with `chars`
+    pub fn to_string_with(&self, chars: &CharMap<T>) -> Result<String, Error> {
         let mut accum = String::new();
+        if self.spots.is_empty() {
+            return Ok(accum);
+        }
 
         let mut min_maxes: Vec<(isize, isize)> = Vec::new();
-        for (coord, _) in self.spots.iter() {
-            if min_maxes.len() == 0 { 
+        for (coord, _) in Storage::iter(&self.spots) {
+            if min_maxes.is_empty() { 
                 min_maxes = coord.dim.iter().map(|_| (0, 0)).collect();
             }
 
@@ -280,14 +425,13 @@ impl<DimensionType> LifeSpace<DimensionType> where
         // swap x and y min / max
         let min_maxes = min_maxes.iter().take(2).rev()
             .chain(min_maxes.iter().skip(2))
-            .map(|s| *s)
+            .copied()
             .collect();
-        println!("min maxes {:?}", min_maxes);
-        self.to_string_rec(&min_maxes, min_maxes.len() - 1, &dummy, &mut accum)?;
+        self.to_string_rec(chars, &min_maxes, min_maxes.len() - 1, &dummy, &mut accum)?;
         Ok(accum)
     }
 
-    pub fn to_string_rec(&self, min_maxes: &Vec<(isize, isize)>, dim: usize, prev_dim_coords: &VecDeque<isize>, accum: &mut String) -> Result<(), Error> {
+    pub fn to_string_rec(&self, chars: &CharMap<T>, min_maxes: &Vec<(isize, isize)>, dim: usize, prev_dim_coords: &VecDeque<isize>, accum: &mut String) -> Result<(), Error> {
         for i in min_maxes[dim].0 ..= min_maxes[dim].1 {
             let mut this_coord = prev_dim_coords.clone();
             this_coord.push_front(i);
@@ -297,18 +441,15 @@ impl<DimensionType> LifeSpace<DimensionType> where
                 let new_coord = 
                     this_coord.iter().take(2).rev()
                     .chain(this_coord.iter().skip(2));
-                let this_coord = Coord::<DimensionType>{dim: new_coord.map(|s| *s).collect(), pd: PhantomData};
-                
-                if let Some(v) = self.spots.get(&this_coord) {
-                    let next_char = match v {
-                        LifeOption::Occupied => '#',
-                        LifeOption::Unoccupied => 'L',
-                        _ =>  '.',
-                    };
-                    accum.push(next_char);
-                } else {
-                    accum.push('.');
+                let mut this_coord = Coord::<N>{dim: [0; N]};
+                for (d, v) in this_coord.dim.iter_mut().zip(new_coord) {
+                    *d = *v;
                 }
+                
+                let next_char = self.spots.get(&this_coord)
+                    .and_then(|v| chars.char_for(*v))
+                    .unwrap_or_else(|| chars.missing());
+                accum.push(next_char);
             } else {
                 if dim > 1 {
                     accum.push_str(&format!(" {}={} ", dim, i));
@@ -318,7 +459,7 @@ impl<DimensionType> LifeSpace<DimensionType> where
                 if dim == 1 {
                     accum.push_str(&format!("x = {} ", i))
                 }                
-                self.to_string_rec(&min_maxes, dim - 1, &this_coord, accum)?;
+                self.to_string_rec(chars, min_maxes, dim - 1, &this_coord, accum)?;
                 if dim == 1 {
                     accum.push('\n');
                 }
@@ -329,11 +470,37 @@ impl<DimensionType> LifeSpace<DimensionType> where
     }
 }
 
-pub struct NeighborIterator<'a, DimensionType> where
-    DimensionType: std::convert::TryInto<usize>,
-    DimensionType: std::convert::TryFrom<usize>
-{
-    spots: &'a HashMap<Coord<DimensionType>, LifeOption>,
+impl<T: CellState, S: Storage<2, T>> LifeSpace<2, T, S> {
+    pub fn at_2d(&self, row: isize, column: isize) -> Option<&T> {
+        self.spots.get(&Coord::new_2d(row, column))
+    }
+}
+
+impl<S: Storage<2>> LifeSpace<2, LifeOption, S> {
+    pub fn neighbors(&self, row: isize, column: isize) -> NeighborIterator<'_, S> {
+        NeighborIterator{spots: &self.spots, topology: &self.topology, row, column, count: 0, skip_chars: None, direction_count: 1}
+    }
+
+    pub fn neighbors_skip_floor(&self, row: isize, column: isize) -> NeighborIterator<'_, S> {
+        NeighborIterator{spots: &self.spots, topology: &self.topology, row, column, count: 0, skip_chars: Some(LifeOption::Floor), direction_count: 1}
+    }
+}
+
+impl<T: CellState, S: Storage<3, T>> LifeSpace<3, T, S> {
+    pub fn at_3d(&self, row: isize, column: isize, z: isize) -> Option<&T> {
+        self.spots.get(&Coord::new_3d(row, column, z))
+    }
+}
+
+impl<T: CellState, S: Storage<4, T>> LifeSpace<4, T, S> {
+    pub fn at_4d(&self, row: isize, column: isize, z: isize, t: isize) -> Option<&T> {
+        self.spots.get(&Coord::new_4d(row, column, z, t))
+    }
+}
+
+pub struct NeighborIterator<'a, S> {
+    spots: &'a S,
+    topology: &'a Topology<2>,
     row: isize, 
     column: isize,
     count: isize,
@@ -341,17 +508,14 @@ pub struct NeighborIterator<'a, DimensionType> where
     direction_count: isize
 }
 
-impl<DimensionType> NeighborIterator<'_, DimensionType> where
-    DimensionType: std::convert::TryInto<usize>,
-    DimensionType: std::convert::TryFrom<usize>
-{
+impl<S> NeighborIterator<'_, S> {
     fn next_direction(&mut self) {
         self.direction_count = 1;
         self.count += 1;
     }
 }
 
-impl Iterator for NeighborIterator<'_, TwoD> {
+impl<S: Storage<2>> Iterator for NeighborIterator<'_, S> {
     type Item = LifeOption;
 
     fn next(&mut self) -> Option<LifeOption> {
@@ -367,23 +531,16 @@ impl Iterator for NeighborIterator<'_, TwoD> {
             _ => return None
         };
 
-        let row = match (self.row as isize) + row_diff {
-            x if x < 0 => {
-                self.next_direction();
-                return self.next()
-            },
-            x => x as isize
-        };
-
-        let column = match (self.column as isize) + column_diff {
-            x if x < 0 => {
+        // past a wall, or (on a torus) all the way round and back to the start
+        let coord = match self.topology.resolve(&Coord::new_2d(self.row + row_diff, self.column + column_diff)) {
+            Some(coord) if coord != Coord::new_2d(self.row, self.column) => coord,
+            _ => {
                 self.next_direction();
                 return self.next()
-            },
-            x => x as isize
+            }
         };
 
-        let value = self.spots.get(&Coord::<TwoD>::new_2d(row, column))
+        let value = self.spots.get(&coord)
             .map(|s| *s);
 
         match value {
@@ -424,9 +581,104 @@ mod tests {
 
     // next up: let's add some 3d tests? I guess for neighbor iter
 
+    #[test]
+    fn test_coord() {
+        let coord = Coord::<4>::from_2d(3, 5);
+        assert_eq!(coord, Coord::new_4d(3, 5, 0, 0));
+        assert_eq!(coord.at(Y), 5);
+        assert_eq!(coord.offset(&[-1, 0, 1, 2]), Coord::from([2, 5, 1, 2]));
+
+        let mut coord = Coord::new_3d(1, 2, 3);
+        coord.set(Z, -3);
+        assert_eq!(coord.dim, [1, 2, -3]);
+    }
+
+    #[test]
+    fn test_neighborhood_moore() {
+        let neighborhood = Neighborhood::<2>::moore(1);
+        let actual: Vec<Coord<2>> = neighborhood.around(Coord::new_2d(0, 0)).collect();
+        assert_eq!(actual, vec!(
+            Coord::new_2d(-1, -1), Coord::new_2d(-1, 0), Coord::new_2d(-1, 1),
+            Coord::new_2d(0, -1),                        Coord::new_2d(0, 1),
+            Coord::new_2d(1, -1),  Coord::new_2d(1, 0),  Coord::new_2d(1, 1),
+        ));
+
+        let original_coord = Coord::new_3d(0, 1, 0);
+        let actual: Vec<Coord<3>> = Neighborhood::<3>::moore(1).around(original_coord).collect();
+        assert_eq!(actual.len(), 26);
+        assert!(!actual.contains(&original_coord));
+
+        assert_eq!(Neighborhood::<4>::moore(1).len(), 80);
+        assert_eq!(Neighborhood::<2>::moore(2).len(), 24);
+    }
+
+    #[test]
+    fn test_neighborhood_von_neumann() {
+        assert_eq!(Neighborhood::<2>::von_neumann(1).len(), 4);
+        assert_eq!(Neighborhood::<2>::von_neumann(2).len(), 12);
+        assert_eq!(Neighborhood::<3>::von_neumann(1).len(), 6);
+        assert!(Neighborhood::<3>::von_neumann(2).offsets().iter().all(|o| o.iter().map(|d| d.abs()).sum::<isize>() <= 2));
+        assert!(Neighborhood::<2>::moore(0).is_empty());
+    }
+
+    #[test]
+    fn test_neighbors_in() {
+        let input = LifeSpace::<2>::new(&example()).unwrap();
+        let moore = Neighborhood::moore(1);
+        let result: Vec<LifeOption> = input.neighbors_in(&Coord::new_2d(1, 1), &moore).collect();
+        assert_eq!(result, input.neighbors(1, 1).collect::<Vec<LifeOption>>());
+
+        let result: Vec<LifeOption> = input.neighbors_in(&Coord::new_2d(9, 0), &moore).collect();
+        assert_eq!(result, vec!(LifeOption::Unoccupied, LifeOption::Floor, LifeOption::Floor));
+    }
+
+    #[test]
+    fn test_neighbors_torus() {
+        let torus = Topology::torus(Coord::new_2d(0, 0), Coord::new_2d(9, 9));
+        let input = LifeSpace::<2>::new(&example()).unwrap().with_topology(torus);
+        let result: Vec<LifeOption> = input.neighbors_in(&Coord::new_2d(9, 0), &Neighborhood::moore(1)).collect();
+        assert_eq!(result, vec!(
+            LifeOption::Unoccupied, LifeOption::Unoccupied, LifeOption::Floor,
+            LifeOption::Unoccupied,                         LifeOption::Floor,
+            LifeOption::Unoccupied, LifeOption::Unoccupied, LifeOption::Floor,
+        ));
+        assert_eq!(result, input.neighbors(9, 0).collect::<Vec<LifeOption>>());
+
+        // looking past floor all the way round comes back to the start, and stops
+        let row = vec!(String::from("L..."));
+        let input = LifeSpace::<2>::new(&row).unwrap()
+            .with_topology(Topology::torus(Coord::new_2d(0, 0), Coord::new_2d(0, 3)));
+        assert_eq!(input.neighbors_skip_floor(0, 0).count(), 0);
+    }
+
+    #[test]
+    fn test_parse_dense() {
+        let sparse = LifeSpace::<2>::new(&example()).unwrap();
+        let dense = LifeSpace::<2, LifeOption, DenseGrid<2>>::new(&example()).unwrap();
+        assert_eq!(dense.spots.extent(), [10, 10]);
+        assert_eq!(dense.at_2d(0, 1), Some(&LifeOption::Floor));
+        assert_eq!(dense.to_string().unwrap(), sparse.to_string().unwrap());
+        assert_eq!(dense.neighbors_skip_floor(1, 1).collect::<Vec<LifeOption>>(), sparse.neighbors_skip_floor(1, 1).collect::<Vec<LifeOption>>());
+
+        let mut dense = dense;
+        let mut assigner = Assigner::new();
+        assigner.assign(-1, 12, LifeOption::Occupied);
+        assigner.commit(&mut dense);
+        assert_eq!(dense.at_2d(-1, 12), Some(&LifeOption::Occupied));
+        assert_eq!(dense.spots.len(), 101);
+    }
+
+    #[test]
+    fn test_parse_4d() {
+        let result = LifeSpace::<4>::new(&example()).unwrap();
+        assert_eq!(result.at_4d(0, 2, 0, 0), Some(&LifeOption::Unoccupied));
+        assert_eq!(result.at(&Coord::new_4d(0, 2, 0, 1)), None);
+        assert!(LifeSpace::<1>::new(&example()).is_err());
+    }
+
     #[test]
     fn test_parse() {
-        let mut result = LifeSpace::<TwoD>::new(&example(), 2).unwrap();
+        let mut result = LifeSpace::<2>::new(&example()).unwrap();
         assert_eq!(result.at_2d(0, 0,), Some(&LifeOption::Unoccupied));
         assert_eq!(result.at_2d(0, 1,), Some(&LifeOption::Floor));
 
@@ -438,7 +690,7 @@ mod tests {
 
     // #[test]
     // fn test_direction_iter() {
-    //     let result = LifeSpace::<TwoD>::new(&example(), 2).unwrap();
+    //     let result = LifeSpace::<2>::new(&example()).unwrap();
     //     let mut iter = DirectionIterator::<TwoD>{
     //         spots: &result.spots,
     //         direction: vec!(1, 1),
@@ -454,7 +706,7 @@ mod tests {
 
     #[test]
     fn test_to_string() {
-        let result = LifeSpace::<TwoD>::new(&example(), 2).unwrap();
+        let result = LifeSpace::<2>::new(&example()).unwrap();
         let mut expected: String = String::from("\n");
         let joined_str: String = example().iter().enumerate().map(|(x, s)| format!("x = {} {}\n", x, s)).collect();
         expected.push_str(joined_str.as_str());
@@ -462,10 +714,16 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn test_to_string_empty() {
+        assert_eq!(LifeSpace::<2>::empty().to_string().unwrap(), "");
+        assert_eq!(LifeSpace::<4>::empty().to_string().unwrap(), "");
+    }
+
     #[test]
     fn test_to_string_3d() { 
-        let mut ls = LifeSpace::<ThreeD>::new(&example(), 3).unwrap();
-        ls.spots.insert(Coord::<ThreeD>::new_3d(0, 0, 1), LifeOption::Occupied);
+        let mut ls = LifeSpace::<3>::new(&example()).unwrap();
+        ls.spots.insert(Coord::new_3d(0, 0, 1), LifeOption::Occupied);
         let result = ls.to_string().unwrap();
         let expected = " 2=0 \nx = 0 L.LL.LL.LL\nx = 1 LLLLLLL.LL\nx = 2 L.L.L..L..\nx = 3 LLLL.LL.LL\nx = 4 L.LL.LL.LL\nx = 5 L.LLLLL.LL\nx = 6 ..L.L.....\nx = 7 LLLLLLLLLL\nx = 8 L.LLLLLL.L\nx = 9 L.LLLLL.LL\n 2=1 \nx = 0 #.........\nx = 1 ..........\nx = 2 ..........\nx = 3 ..........\nx = 4 ..........\nx = 5 ..........\nx = 6 ..........\nx = 7 ..........\nx = 8 ..........\nx = 9 ..........\n";
         assert_eq!(result, expected);
@@ -473,7 +731,7 @@ mod tests {
 
     #[test]
     fn test_neighbors() {
-        let mut input = LifeSpace::<TwoD>::new(&example(), 2).unwrap();
+        let mut input = LifeSpace::<2>::new(&example()).unwrap();
         let iter = input.neighbors(1, 1);
         let result: Vec<LifeOption> = iter.collect();
         assert_eq!(result, vec!(
@@ -485,7 +743,7 @@ mod tests {
 
     #[test]
     fn test_neighbors_limits() {
-        let mut input = LifeSpace::<TwoD>::new(&example(), 2).unwrap();
+        let mut input = LifeSpace::<2>::new(&example()).unwrap();
         let iter = input.neighbors(9, 0);
         let result: Vec<LifeOption> = iter.collect();
         assert_eq!(result, vec!(
@@ -495,7 +753,7 @@ mod tests {
 
     #[test]
     fn test_neighbors_right_limit() {
-        let mut input = LifeSpace::<TwoD>::new(&example(), 2).unwrap();
+        let mut input = LifeSpace::<2>::new(&example()).unwrap();
         let iter = input.neighbors(7, 9);
         let result: Vec<LifeOption> = iter.collect();
         assert_eq!(result, vec!(
@@ -518,7 +776,7 @@ mod tests {
             String::from("#........"),
             String::from("...#....."),
         );
-        let input = LifeSpace::<TwoD>::new(&example, 2).unwrap();
+        let input = LifeSpace::<2>::new(&example).unwrap();
         let iter = input.neighbors_skip_floor(4, 3);
         let result: Vec<LifeOption> = iter.collect();
         assert_eq!(result, vec!(
@@ -535,7 +793,7 @@ mod tests {
             String::from(".L.L.#.#.#.#."),
             String::from("............."),
         );
-        let input = LifeSpace::<TwoD>::new(&example, 2).unwrap();
+        let input = LifeSpace::<2>::new(&example).unwrap();
         let iter = input.neighbors_skip_floor(1, 1);
         let result: Vec<LifeOption> = iter.collect();
         assert_eq!(result, vec!(
@@ -554,7 +812,7 @@ mod tests {
             String::from("#.#.#.#"),
             String::from(".##.##."),
         );
-        let input = LifeSpace::<TwoD>::new(&example, 2).unwrap();
+        let input = LifeSpace::<2>::new(&example).unwrap();
         let iter = input.neighbors_skip_floor(3, 3);
         let result: Vec<LifeOption> = iter.collect();
         assert_eq!(result, vec!());
@@ -0,0 +1,132 @@
+use super::{CellState, LifeSpace, Neighbors, Rule, Storage};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+/// Where a simulation ended up, see `LifeSpace::run_until_cycle`
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Evolution {
+    /// Nothing changes after generation `at`
+    FixedPoint{at: usize},
+    /// From generation `start` on, the space repeats every `period` generations
+    Oscillating{start: usize, period: usize},
+    /// Still changing at generation `generation`, when the cap was hit
+    Evolving{generation: usize},
+}
+
+impl std::fmt::Display for Evolution {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Evolution::FixedPoint{at} => write!(f, "fixed point from generation {}", at),
+            Evolution::Oscillating{start, period} => write!(f, "period {} oscillation from generation {}", period, start),
+            Evolution::Evolving{generation} => write!(f, "still evolving at generation {}", generation),
+        }
+    }
+}
+
+impl<const N: usize, T: CellState, S: Storage<N, T>> LifeSpace<N, T, S> {
+    /// A hash of every cell's coord and state, ignoring cells in the `background` state.
+    /// Doesn't depend on the order cells are stored in, so equal generations hash the same.
+    pub fn fingerprint(&self, background: Option<T>) -> u64 {
+        Storage::iter(&self.spots)
+            .filter(|(_, value)| Some(**value) != background)
+            .map(|(coord, value)| {
+                let mut hasher = DefaultHasher::new();
+                coord.hash(&mut hasher);
+                value.hash(&mut hasher);
+                hasher.finish()
+            })
+            .fold(0, u64::wrapping_add)
+    }
+
+    /// Step until the space stops changing or repeats an earlier generation, or until
+    /// `max_generations` steps have gone by.  Generations are compared by `fingerprint`,
+    /// ignoring cells in the rule's background state, so in principle (though with 64 bit
+    /// hashes, not in practice) two different generations could be mistaken for a cycle.
+    /// Examples:
+    /// ```
+    /// use advent_2020_common::life::{Evolution, LifeRule, LifeSpace, Neighborhood};
+    /// let blinker = vec!(String::from("###"));
+    /// let mut space = LifeSpace::<2>::new(&blinker).unwrap();
+    /// let result = space.run_until_cycle(&Neighborhood::moore(1), &LifeRule::conway(), 100);
+    /// assert_eq!(result, Evolution::Oscillating{start: 0, period: 2});
+    /// ```
+    ///
+    pub fn run_until_cycle<B: Neighbors<N, T>, R: Rule<T>>(&mut self, neighbors: &B, rule: &R, max_generations: usize) -> Evolution {
+        let background = rule.background();
+        let mut seen: HashMap<u64, usize> = HashMap::new();
+        seen.insert(self.fingerprint(background), self.generation);
+
+        for _ in 0 .. max_generations {
+            if self.step(neighbors, rule) == 0 {
+                return Evolution::FixedPoint{at: self.generation - 1};
+            }
+
+            if let Some(start) = seen.insert(self.fingerprint(background), self.generation) {
+                return match self.generation - start {
+                    1 => Evolution::FixedPoint{at: start},
+                    period => Evolution::Oscillating{start, period},
+                };
+            }
+        }
+
+        Evolution::Evolving{generation: self.generation}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::life::{LifeOption, LifeRule, Neighborhood};
+
+    fn lines(input: &[&str]) -> Vec<String> {
+        input.iter().map(|s| String::from(*s)).collect()
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let a = LifeSpace::<2>::new(&lines(&["#.", ".#"])).unwrap();
+        let b = LifeSpace::<2>::new(&lines(&["#.", ".#", ".."])).unwrap();
+        let c = LifeSpace::<2>::new(&lines(&[".#", "#."])).unwrap();
+        assert_eq!(a.fingerprint(Some(LifeOption::Floor)), b.fingerprint(Some(LifeOption::Floor)));
+        assert_ne!(a.fingerprint(None), b.fingerprint(None));
+        assert_ne!(a.fingerprint(Some(LifeOption::Floor)), c.fingerprint(Some(LifeOption::Floor)));
+    }
+
+    #[test]
+    fn test_fixed_point() {
+        let mut block = LifeSpace::<2>::new(&lines(&["##", "##"])).unwrap();
+        let result = block.run_until_cycle(&Neighborhood::moore(1), &LifeRule::conway(), 10);
+        assert_eq!(result, Evolution::FixedPoint{at: 0});
+
+        // dies off, leaving nothing but background
+        let mut pair = LifeSpace::<2>::new(&lines(&["##"])).unwrap();
+        let result = pair.run_until_cycle(&Neighborhood::moore(1), &LifeRule::conway(), 10);
+        assert_eq!(result, Evolution::FixedPoint{at: 1});
+        assert_eq!(result.to_string(), "fixed point from generation 1");
+    }
+
+    #[test]
+    fn test_oscillation_after_start() {
+        // the lone cell dies, leaving a blinker which first repeats at generation 1
+        let mut space = LifeSpace::<2>::new(&lines(&["###..#"])).unwrap();
+        let result = space.run_until_cycle(&Neighborhood::moore(1), &LifeRule::conway(), 10);
+        assert_eq!(result, Evolution::Oscillating{start: 1, period: 2});
+        assert_eq!(space.generation, 3);
+        assert_eq!(result.to_string(), "period 2 oscillation from generation 1");
+
+        // an L tromino fills in to a block
+        let mut space = LifeSpace::<2>::new(&lines(&["##", "#."])).unwrap();
+        let result = space.run_until_cycle(&Neighborhood::moore(1), &LifeRule::conway(), 10);
+        assert_eq!(result, Evolution::FixedPoint{at: 1});
+    }
+
+    #[test]
+    fn test_evolving() {
+        let glider = lines(&[".#.", "..#", "###"]);
+        let mut space = LifeSpace::<2>::new(&glider).unwrap();
+        let result = space.run_until_cycle(&Neighborhood::moore(1), &LifeRule::conway(), 20);
+        assert_eq!(result, Evolution::Evolving{generation: 20});
+        assert_eq!(space.count(LifeOption::Occupied), 5);
+    }
+}
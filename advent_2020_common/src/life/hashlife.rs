@@ -0,0 +1,444 @@
+use super::{Coord, LifeOption, LifeRule, LifeSpace, Storage, X, Y};
+use crate::Error;
+use std::collections::HashMap;
+
+type NodeId = u32;
+
+const DEAD: NodeId = 0;
+const ALIVE: NodeId = 1;
+
+/// Past this the corners of the space no longer fit in an `isize`
+const MAX_LEVEL: u8 = 60;
+
+/// A square of `2^level` cells on a side, split into four quarters a level down; level 0
+/// nodes are single cells.  Nodes are shared, so equal squares are the same node.
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    level: u8,
+    // nw, ne, sw, se
+    children: [NodeId; 4],
+    population: u64,
+}
+
+/// A two state 2d Life space stored as a quadtree of shared nodes, with the result of
+/// stepping each node memoised (Gosper's Hashlife).  Patterns with a lot of repetition,
+/// in space or time, can be moved on billions of generations in a few milliseconds.
+/// Occupied cells are alive and everything else is dead.
+/// Examples:
+/// ```
+/// use advent_2020_common::life::{HashLife, LifeOption, LifeRule, LifeSpace};
+/// let glider = vec!(String::from(".#."), String::from("..#"), String::from("###"));
+/// let space = LifeSpace::<2>::new(&glider).unwrap();
+/// let mut life = HashLife::from_space(&space, &LifeRule::conway()).unwrap();
+///
+/// // a glider moves a cell down and right every 4 generations
+/// life.advance(1_000_000_000).unwrap();
+/// let moved: LifeSpace<2> = life.to_space();
+/// assert_eq!(moved.generation, 1_000_000_000);
+/// assert_eq!(moved.count(LifeOption::Occupied), 5);
+/// assert_eq!(moved.at_2d(250_000_002, 250_000_002), Some(&LifeOption::Occupied));
+/// ```
+///
+pub struct HashLife {
+    // bitmasks of the live neighbour counts that give birth to / keep alive a cell
+    birth: u16,
+    survival: u16,
+    nodes: Vec<Node>,
+    index: HashMap<[NodeId; 4], NodeId>,
+    empty: Vec<NodeId>,
+    results: HashMap<(NodeId, u8), NodeId>,
+    root: NodeId,
+    // the row and column of the root's top left cell
+    origin: (isize, isize),
+    generation: u64,
+}
+
+impl HashLife {
+    /// An empty space under `rule`.  Rules where cells are born with no neighbours (B0)
+    /// would fill the infinite plane, so aren't supported.
+    pub fn new(rule: &LifeRule) -> Result<Self, Error> {
+        let mask = |counts: &[usize]| -> Result<u16, Error> {
+            counts.iter().try_fold(0, |mask, count| match count {
+                0 ..= 8 => Ok(mask | 1 << count),
+                _ => Err(Error::invalid_input(format!("rule {} has more than 8 neighbours", rule))),
+            })
+        };
+        if rule.birth.contains(&0) {
+            return Err(Error::invalid_input(format!("rule {} fills empty space, so can't be run with hashlife", rule)));
+        }
+
+        let dead = Node{level: 0, children: [DEAD; 4], population: 0};
+        let alive = Node{level: 0, children: [DEAD; 4], population: 1};
+        let mut life = HashLife{
+            birth: mask(&rule.birth)?,
+            survival: mask(&rule.survival)?,
+            nodes: vec!(dead, alive),
+            index: HashMap::new(),
+            empty: vec!(DEAD),
+            results: HashMap::new(),
+            root: DEAD,
+            origin: (0, 0),
+            generation: 0,
+        };
+        life.root = life.empty(3);
+        Ok(life)
+    }
+
+    /// Load the occupied cells of `space`, and its generation
+    pub fn from_space<S: Storage<2>>(space: &LifeSpace<2, LifeOption, S>, rule: &LifeRule) -> Result<Self, Error> {
+        let mut life = HashLife::new(rule)?;
+        let cells: Vec<(isize, isize)> = Storage::iter(&space.spots)
+            .filter(|(_, state)| **state == LifeOption::Occupied)
+            .map(|(coord, _)| (coord.at(X), coord.at(Y)))
+            .collect();
+
+        if let (Some(top), Some(left)) = (cells.iter().map(|c| c.0).min(), cells.iter().map(|c| c.1).min()) {
+            let bottom = cells.iter().map(|c| c.0).max().unwrap();
+            let right = cells.iter().map(|c| c.1).max().unwrap();
+            // abs_diff, since the span of a space wider than isize::MAX doesn't fit in one
+            let size = bottom.abs_diff(top).max(right.abs_diff(left));
+            let mut level = 3;
+            while (1usize << level) <= size {
+                level += 1;
+                if level > MAX_LEVEL {
+                    return Err(Error::invalid_input("space is too big for hashlife"));
+                }
+            }
+
+            life.origin = (top, left);
+            life.root = life.build(level, top, left, &cells);
+        }
+
+        life.generation = space.generation as u64;
+        Ok(life)
+    }
+
+    /// The live cells as a `LifeSpace` of occupied cells, at the same generation
+    pub fn to_space<S: Storage<2>>(&self) -> LifeSpace<2, LifeOption, S> {
+        let mut space = LifeSpace::<2, LifeOption, S>::empty();
+        let mut cells = Vec::new();
+        self.collect(self.root, self.origin.0, self.origin.1, &mut cells);
+        for (row, column) in cells {
+            space.spots.insert(Coord::new_2d(row, column), LifeOption::Occupied);
+        }
+        space.generation = self.generation as usize;
+        space
+    }
+
+    pub fn population(&self) -> u64 {
+        self.nodes[self.root as usize].population
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Move on `2^k` generations in one go
+    pub fn jump(&mut self, k: u8) -> Result<(), Error> {
+        if k > MAX_LEVEL - 3 {
+            return Err(Error::invalid_input(format!("can't jump 2^{} generations at once", k)));
+        }
+
+        // grow until every live cell is in the centre half of the root, and the root is
+        // big enough to step 2^k generations, then once more so cells can't spread past
+        // the centre half that stepping returns
+        while self.level() < k + 2 || !self.centred() {
+            self.expand()?;
+        }
+        self.expand()?;
+
+        let quarter = 1 << (self.level() - 2);
+        self.root = self.step(self.root, k);
+        self.origin = (self.origin.0 + quarter, self.origin.1 + quarter);
+        self.generation += 1 << k;
+        Ok(())
+    }
+
+    /// Move on `generations` generations, a power of 2 at a time
+    pub fn advance(&mut self, generations: u64) -> Result<(), Error> {
+        for k in 0 .. 64 {
+            if generations & (1 << k) != 0 {
+                self.jump(k)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn level(&self) -> u8 {
+        self.nodes[self.root as usize].level
+    }
+
+    fn children(&self, node: NodeId) -> [NodeId; 4] {
+        self.nodes[node as usize].children
+    }
+
+    fn join(&mut self, children: [NodeId; 4]) -> NodeId {
+        if let Some(id) = self.index.get(&children) {
+            return *id;
+        }
+
+        let level = self.nodes[children[0] as usize].level + 1;
+        let population = children.iter().map(|c| self.nodes[*c as usize].population).sum();
+        let id = self.nodes.len() as NodeId;
+        self.nodes.push(Node{level, children, population});
+        self.index.insert(children, id);
+        id
+    }
+
+    fn empty(&mut self, level: u8) -> NodeId {
+        while self.empty.len() <= level as usize {
+            let below = *self.empty.last().unwrap();
+            let next = self.join([below; 4]);
+            self.empty.push(next);
+        }
+        self.empty[level as usize]
+    }
+
+    // the node `2^level` on a side with its top left at `top`, `left` holding `cells`
+    fn build(&mut self, level: u8, top: isize, left: isize, cells: &[(isize, isize)]) -> NodeId {
+        if cells.is_empty() {
+            return self.empty(level);
+        }
+        if level == 0 {
+            return ALIVE;
+        }
+
+        let half = 1 << (level - 1);
+        let mut quarters = [Vec::new(), Vec::new(), Vec::new(), Vec::new()];
+        for cell in cells {
+            let quarter = (if cell.0 < top + half { 0 } else { 2 }) + (if cell.1 < left + half { 0 } else { 1 });
+            quarters[quarter].push(*cell);
+        }
+
+        let nw = self.build(level - 1, top, left, &quarters[0]);
+        let ne = self.build(level - 1, top, left + half, &quarters[1]);
+        let sw = self.build(level - 1, top + half, left, &quarters[2]);
+        let se = self.build(level - 1, top + half, left + half, &quarters[3]);
+        self.join([nw, ne, sw, se])
+    }
+
+    fn collect(&self, node: NodeId, top: isize, left: isize, cells: &mut Vec<(isize, isize)>) {
+        let Node{level, children, population} = self.nodes[node as usize];
+        if population == 0 {
+            return;
+        }
+        if level == 0 {
+            cells.push((top, left));
+            return;
+        }
+
+        let half = 1 << (level - 1);
+        self.collect(children[0], top, left, cells);
+        self.collect(children[1], top, left + half, cells);
+        self.collect(children[2], top + half, left, cells);
+        self.collect(children[3], top + half, left + half, cells);
+    }
+
+    // whether every live cell is in the centre half of the root
+    fn centred(&self) -> bool {
+        let [nw, ne, sw, se] = self.children(self.root);
+        let inner = [self.children(nw)[3], self.children(ne)[2], self.children(sw)[1], self.children(se)[0]];
+        inner.iter().map(|i| self.nodes[*i as usize].population).sum::<u64>() == self.population()
+    }
+
+    // put the root in the middle of a node twice its size
+    fn expand(&mut self) -> Result<(), Error> {
+        let level = self.level();
+        if level >= MAX_LEVEL {
+            return Err(Error::invalid_input("pattern has grown too big for hashlife"));
+        }
+
+        let e = self.empty(level - 1);
+        let [nw, ne, sw, se] = self.children(self.root);
+        let children = [
+            self.join([e, e, e, nw]),
+            self.join([e, e, ne, e]),
+            self.join([e, sw, e, e]),
+            self.join([se, e, e, e]),
+        ];
+        self.root = self.join(children);
+
+        let half = 1 << (level - 1);
+        self.origin = (self.origin.0 - half, self.origin.1 - half);
+        Ok(())
+    }
+
+    // the centre half of a node, as it is now
+    fn centre(&mut self, node: NodeId) -> NodeId {
+        let [nw, ne, sw, se] = self.children(node);
+        self.join([self.children(nw)[3], self.children(ne)[2], self.children(sw)[1], self.children(se)[0]])
+    }
+
+    // the centre half of a node of level 2 or more, `2^k` generations on, where `k` is at
+    // most level - 2
+    fn step(&mut self, node: NodeId, k: u8) -> NodeId {
+        let Node{level, children, population} = self.nodes[node as usize];
+        if population == 0 {
+            return self.empty(level - 1);
+        }
+        if let Some(result) = self.results.get(&(node, k)) {
+            return *result;
+        }
+
+        let result = if level == 2 {
+            self.step_cells(node)
+        } else {
+            let [a, b, c, d] = children;
+            let [_, a_ne, a_sw, a_se] = self.children(a);
+            let [b_nw, _, b_sw, b_se] = self.children(b);
+            let [c_nw, c_ne, _, c_se] = self.children(c);
+            let [d_nw, d_ne, d_sw, _] = self.children(d);
+
+            // nine overlapping squares half the size of this one
+            let nine = [
+                a,
+                self.join([a_ne, b_nw, a_se, b_sw]),
+                b,
+                self.join([a_sw, a_se, c_nw, c_ne]),
+                self.join([a_se, b_sw, c_ne, d_nw]),
+                self.join([b_sw, b_se, d_nw, d_ne]),
+                c,
+                self.join([c_ne, d_nw, c_se, d_sw]),
+                d,
+            ];
+
+            // at full speed both halves of the work move time on; going slower, the first
+            // half just takes the centres
+            let full_speed = k == level - 2;
+            let mut moved = [DEAD; 9];
+            for (m, n) in moved.iter_mut().zip(nine.iter()) {
+                *m = if full_speed { self.step(*n, k - 1) } else { self.centre(*n) };
+            }
+
+            let quarters = [
+                self.join([moved[0], moved[1], moved[3], moved[4]]),
+                self.join([moved[1], moved[2], moved[4], moved[5]]),
+                self.join([moved[3], moved[4], moved[6], moved[7]]),
+                self.join([moved[4], moved[5], moved[7], moved[8]]),
+            ];
+            let second = if full_speed { k - 1 } else { k };
+            let mut result = [DEAD; 4];
+            for (r, q) in result.iter_mut().zip(quarters.iter()) {
+                *r = self.step(*q, second);
+            }
+            self.join(result)
+        };
+
+        self.results.insert((node, k), result);
+        result
+    }
+
+    // the centre 2x2 of a 4x4 node, one generation on, worked out cell by cell
+    fn step_cells(&mut self, node: NodeId) -> NodeId {
+        let mut grid = [[false; 4]; 4];
+        for (q, quarter) in self.children(node).iter().enumerate() {
+            for (i, cell) in self.children(*quarter).iter().enumerate() {
+                grid[(q / 2) * 2 + i / 2][(q % 2) * 2 + i % 2] = *cell == ALIVE;
+            }
+        }
+
+        let mut next = [DEAD; 4];
+        for (i, n) in next.iter_mut().enumerate() {
+            let (row, column) = (1 + i / 2, 1 + i % 2);
+            let alive = (row - 1 ..= row + 1)
+                .flat_map(|r| (column - 1 ..= column + 1).map(move |c| (r, c)))
+                .filter(|(r, c)| (*r, *c) != (row, column) && grid[*r][*c])
+                .count();
+            let mask = if grid[row][column] { self.survival } else { self.birth };
+            if mask & (1 << alive) != 0 {
+                *n = ALIVE;
+            }
+        }
+        self.join(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::life::Neighborhood;
+
+    fn occupied(space: &LifeSpace<2>) -> Vec<Coord<2>> {
+        let mut cells: Vec<Coord<2>> = space.spots.iter()
+            .filter(|(_, state)| **state == LifeOption::Occupied)
+            .map(|(coord, _)| *coord)
+            .collect();
+        cells.sort();
+        cells
+    }
+
+    // a fixed pseudo-random soup
+    fn soup(size: usize) -> Vec<String> {
+        let mut seed: u64 = 0x9e37_79b9_7f4a_7c15;
+        (0 .. size).map(|_| (0 .. size).map(|_| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            if seed.is_multiple_of(3) { '#' } else { '.' }
+        }).collect()).collect()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let space = LifeSpace::<2>::new(&soup(20)).unwrap();
+        let life = HashLife::from_space(&space, &LifeRule::conway()).unwrap();
+        assert_eq!(life.population() as usize, space.count(LifeOption::Occupied));
+        assert_eq!(occupied(&life.to_space()), occupied(&space));
+
+        let empty = HashLife::new(&LifeRule::conway()).unwrap();
+        assert_eq!(empty.to_space::<HashMap<Coord<2>, LifeOption>>().spots.len(), 0);
+    }
+
+    #[test]
+    fn test_matches_stepping() {
+        let rules = [LifeRule::conway(), LifeRule::parse("B36/S23").unwrap(), LifeRule::parse("B2/S").unwrap()];
+        for rule in rules.iter() {
+            let mut space = LifeSpace::<2>::new(&soup(16)).unwrap();
+            let mut life = HashLife::from_space(&space, rule).unwrap();
+            for generations in [1, 2, 5, 8, 21].iter() {
+                space.run(&Neighborhood::moore(1), rule, *generations);
+                life.advance(*generations as u64).unwrap();
+                assert_eq!(life.generation(), space.generation as u64);
+                assert_eq!(occupied(&life.to_space()), occupied(&space), "rule {} at generation {}", rule, space.generation);
+            }
+        }
+    }
+
+    #[test]
+    fn test_jump() {
+        let blinker = vec!(String::from("###"));
+        let space = LifeSpace::<2>::new(&blinker).unwrap();
+        let mut life = HashLife::from_space(&space, &LifeRule::conway()).unwrap();
+        life.jump(0).unwrap();
+        assert_eq!(occupied(&life.to_space()), vec!(Coord::new_2d(-1, 1), Coord::new_2d(0, 1), Coord::new_2d(1, 1)));
+        life.jump(40).unwrap();
+        assert_eq!(life.generation(), (1 << 40) + 1);
+        assert_eq!(occupied(&life.to_space()).len(), 3);
+        assert_eq!(life.to_space::<HashMap<Coord<2>, LifeOption>>().at_2d(-1, 1), Some(&LifeOption::Occupied));
+    }
+
+    #[test]
+    fn test_unsupported_rules() {
+        assert!(HashLife::new(&LifeRule::parse("B03/S23").unwrap()).is_err());
+        assert!(HashLife::new(&LifeRule{birth: vec!(9), survival: vec!()}).is_err());
+        assert!(HashLife::new(&LifeRule::conway()).unwrap().jump(64).is_err());
+    }
+
+    #[test]
+    fn test_too_big() {
+        let rule = LifeRule::conway();
+        let corners = |min: isize, max: isize| {
+            let mut space = LifeSpace::<2>::empty();
+            space.spots.insert(Coord::new_2d(min, min), LifeOption::Occupied);
+            space.spots.insert(Coord::new_2d(max, max), LifeOption::Occupied);
+            space
+        };
+        // wider than isize::MAX, so the span alone would overflow
+        assert!(HashLife::from_space(&corners(isize::MIN, isize::MAX), &rule).is_err());
+        assert!(HashLife::from_space(&corners(-isize::MAX / 2, isize::MAX / 2), &rule).is_err());
+        assert!(HashLife::from_space(&corners(0, 1 << 60), &rule).is_err());
+
+        let life = HashLife::from_space(&corners(0, (1 << 60) - 1), &rule).unwrap();
+        assert_eq!(life.population(), 2);
+    }
+}
@@ -0,0 +1,230 @@
+use super::{Coord, Neighborhood, NeighborhoodKind, X, Y};
+use crate::Error;
+
+/// The six ways out of a hexagonal cell, in a grid whose rows run east - west
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum HexDirection {
+    E,
+    SE,
+    SW,
+    W,
+    NW,
+    NE,
+}
+
+impl HexDirection {
+    pub const ALL: [HexDirection; 6] = [
+        HexDirection::E, HexDirection::SE, HexDirection::SW,
+        HexDirection::W, HexDirection::NW, HexDirection::NE,
+    ];
+
+    /// The change in axial coords `(q, r)` from stepping this way
+    pub fn axial(&self) -> (isize, isize) {
+        match self {
+            HexDirection::E => (1, 0),
+            HexDirection::SE => (0, 1),
+            HexDirection::SW => (-1, 1),
+            HexDirection::W => (-1, 0),
+            HexDirection::NW => (0, -1),
+            HexDirection::NE => (1, -1),
+        }
+    }
+
+    /// Split a run of directions with no separators, like `esenee`, into its steps
+    /// Examples:
+    /// ```
+    /// use advent_2020_common::life::HexDirection;
+    /// let path = HexDirection::parse_path("nwwswee").unwrap();
+    /// assert_eq!(path, vec!(HexDirection::NW, HexDirection::W, HexDirection::SW, HexDirection::E, HexDirection::E));
+    /// assert!(HexDirection::parse_path("nwn").is_err());
+    /// ```
+    ///
+    pub fn parse_path(path: &str) -> Result<Vec<HexDirection>, Error> {
+        let mut result = Vec::new();
+        let mut chars = path.trim().chars().enumerate();
+
+        while let Some((i, c)) = chars.next() {
+            let direction = match c {
+                'e' => HexDirection::E,
+                'w' => HexDirection::W,
+                'n' | 's' => match (c, chars.next().map(|(_, c2)| c2)) {
+                    ('n', Some('e')) => HexDirection::NE,
+                    ('n', Some('w')) => HexDirection::NW,
+                    ('s', Some('e')) => HexDirection::SE,
+                    ('s', Some('w')) => HexDirection::SW,
+                    _ => return Err(Error::parse(format!("'{}' must be followed by 'e' or 'w'", c)).at(1, i + 1)),
+                },
+                _ => return Err(Error::parse(format!("unknown direction {}", c)).at(1, i + 1)),
+            };
+            result.push(direction);
+        }
+
+        Ok(result)
+    }
+}
+
+impl Coord<2> {
+    /// The hex at axial coords `q` (counting east) and `r` (counting south east), kept as
+    /// row `r` and column `q` so a hex space prints and draws as a skewed grid
+    pub fn hex(q: isize, r: isize) -> Self {
+        Coord::new_2d(r, q)
+    }
+
+    pub fn q(&self) -> isize {
+        self.dim[Y]
+    }
+
+    pub fn r(&self) -> isize {
+        self.dim[X]
+    }
+
+    /// Cube coords `[q, r, s]`, where `q + r + s == 0`
+    pub fn cube(&self) -> [isize; 3] {
+        [self.q(), self.r(), -self.q() - self.r()]
+    }
+
+    /// The hex at cube coords `[q, r, s]`, or `None` if they don't add up to 0
+    pub fn from_cube(cube: [isize; 3]) -> Option<Self> {
+        match cube {
+            [q, r, s] if q + r + s == 0 => Some(Coord::hex(q, r)),
+            _ => None,
+        }
+    }
+
+    /// The neighbouring hex in `direction`
+    pub fn hex_step(&self, direction: HexDirection) -> Self {
+        let (q, r) = direction.axial();
+        Coord::hex(self.q() + q, self.r() + r)
+    }
+
+    /// Where following `path` from this hex ends up
+    pub fn hex_walk(&self, path: &[HexDirection]) -> Self {
+        path.iter().fold(*self, |coord, direction| coord.hex_step(*direction))
+    }
+
+    /// The fewest steps between two hexes
+    /// Examples:
+    /// ```
+    /// use advent_2020_common::life::{Coord, HexDirection};
+    /// let end = Coord::hex(0, 0).hex_walk(&HexDirection::parse_path("nwwswee").unwrap());
+    /// assert_eq!(end, Coord::hex(0, 0));
+    /// let far = Coord::hex(0, 0).hex_walk(&HexDirection::parse_path("eeesw").unwrap());
+    /// assert_eq!(far.hex_distance(&Coord::hex(0, 0)), 3);
+    /// ```
+    ///
+    pub fn hex_distance(&self, other: &Coord<2>) -> usize {
+        hex_length(self.r() - other.r(), self.q() - other.q())
+    }
+}
+
+/// Steps from the origin to a hex `rows` rows and `columns` columns away in axial coords
+pub(super) fn hex_length(rows: isize, columns: isize) -> usize {
+    ((rows.abs() + columns.abs() + (rows + columns).abs()) / 2) as usize
+}
+
+impl Neighborhood<2> {
+    /// Every hex within `radius` steps; the nearest 6 for a radius of 1
+    pub fn hex(radius: usize) -> Self {
+        Neighborhood::new(NeighborhoodKind::Hex, radius)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::life::{Assigner, LifeOption, LifeRule, LifeSpace};
+
+    fn example() -> Vec<&'static str> {
+        vec!(
+            "sesenwnenenewseeswwswswwnenewsewsw",
+            "neeenesenwnwwswnenewnwwsewnenwseswesw",
+            "seswneswswsenwwnwse",
+            "nwnwneseeswswnenewneswwnewseswneseene",
+            "swweswneswnenwsewnwneneseenw",
+            "eesenwseswswnenwswnwnwsewwnwsene",
+            "sewnenenenesenwsewnenwwwse",
+            "wenwwweseeeweswwwnwwe",
+            "wsweesenenewnwwnwsenewsenwwsesesenwne",
+            "neeswseenwwswnwswswnw",
+            "nenwswwsewswnenenewsenwsenwnesesenew",
+            "enewnwewneswsewnwswenweswnenwsenwsw",
+            "sweneswneswneneenwnewenewwneswswnese",
+            "swwesenesewenwneswnwwneseswwne",
+            "enesenwswwswneneswsenwnewswseenwsese",
+            "wnwnesenesenenwwnenwsewesewsesesew",
+            "nenewswnwewswnenesenwnesewesw",
+            "eneswnwswnwsenenwnwnwwseeswneewsenese",
+            "neswnwewnwnwseenwseesewsenwsweewe",
+            "wseweeenwnesenwwwswnew",
+        )
+    }
+
+    // black tiles are occupied; a tile flipped back to white is floor
+    fn flip_tiles(paths: &[&str]) -> LifeSpace<2> {
+        let mut space = LifeSpace::<2>::new(&[]).unwrap();
+        for path in paths {
+            let tile = Coord::hex(0, 0).hex_walk(&HexDirection::parse_path(path).unwrap());
+            let flipped = match space.at(&tile) {
+                Some(LifeOption::Occupied) => LifeOption::Floor,
+                _ => LifeOption::Occupied,
+            };
+            let mut assigner = Assigner::new();
+            assigner.insert(tile, flipped);
+            assigner.commit(&mut space);
+        }
+        space
+    }
+
+    #[test]
+    fn test_parse_path() {
+        let path = HexDirection::parse_path("esenee").unwrap();
+        assert_eq!(path, vec!(HexDirection::E, HexDirection::SE, HexDirection::NE, HexDirection::E));
+        assert_eq!(Coord::hex(0, 0).hex_walk(&path), Coord::hex(3, 0));
+        assert_eq!(Coord::hex(0, 0).hex_walk(&HexDirection::parse_path("esew").unwrap()), Coord::hex(0, 1));
+
+        let err = HexDirection::parse_path("enex").unwrap_err();
+        assert_eq!(err.to_string(), "unknown direction x (line 1, column 4)");
+        assert!(HexDirection::parse_path("es").is_err());
+    }
+
+    #[test]
+    fn test_cube() {
+        let coord = Coord::hex(2, -5);
+        assert_eq!(coord.cube(), [2, -5, 3]);
+        assert_eq!(Coord::from_cube(coord.cube()), Some(coord));
+        assert_eq!(Coord::from_cube([1, 1, 1]), None);
+
+        for direction in HexDirection::ALL.iter() {
+            let next = coord.hex_step(*direction);
+            assert_eq!(next.hex_distance(&coord), 1);
+            assert_eq!(next.cube().iter().sum::<isize>(), 0);
+        }
+    }
+
+    #[test]
+    fn test_hex_neighborhood() {
+        let hex = Neighborhood::hex(1);
+        assert_eq!(hex.len(), 6);
+        let mut around: Vec<Coord<2>> = hex.around(Coord::hex(0, 0)).collect();
+        let mut steps: Vec<Coord<2>> = HexDirection::ALL.iter().map(|d| Coord::hex(0, 0).hex_step(*d)).collect();
+        around.sort();
+        steps.sort();
+        assert_eq!(around, steps);
+
+        assert_eq!(Neighborhood::hex(2).len(), 18);
+    }
+
+    #[test]
+    fn test_hex_life() {
+        let mut space = flip_tiles(&example());
+        assert_eq!(space.count(LifeOption::Occupied), 10);
+
+        // black tiles with no or more than 2 black neighbours turn white, and white tiles
+        // with exactly 2 turn black
+        let rule = LifeRule::parse("B2/S12").unwrap();
+        space.step(&Neighborhood::hex(1), &rule);
+        assert_eq!(space.count(LifeOption::Occupied), 15);
+        space.run(&Neighborhood::hex(1), &rule, 9);
+        assert_eq!(space.count(LifeOption::Occupied), 37);
+    }
+}
@@ -0,0 +1,440 @@
+use super::{CellState, Coord, LifeOption, LifeSpace, Neighbors, Rule, Storage, X, Y, Z};
+use crate::Error;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// A colour as red, green and blue bytes
+pub type Rgb = [u8; 3];
+
+/// Which colour each cell state is drawn in, plus the colours for cells missing from
+/// the space and for the border between panels, and how many pixels wide a cell is.
+/// Examples:
+/// ```
+/// use advent_2020_common::life::{LifeOption, Palette};
+/// let palette = Palette::new(&[(LifeOption::Occupied, [255, 255, 255])]).with_scale(1);
+/// assert_eq!(palette.color(Some(LifeOption::Occupied)), [255, 255, 255]);
+/// assert_eq!(palette.color(Some(LifeOption::Floor)), palette.color(None));
+/// ```
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette<T> {
+    colors: Vec<(T, Rgb)>,
+    missing: Rgb,
+    border: Rgb,
+    scale: usize,
+}
+
+impl<T: CellState> Palette<T> {
+    /// Black for missing cells, grey borders and 4 pixels a cell, unless changed
+    pub fn new(colors: &[(T, Rgb)]) -> Self {
+        Palette{colors: colors.to_vec(), missing: [0, 0, 0], border: [128, 128, 128], scale: 4}
+    }
+
+    pub fn with_missing(mut self, missing: Rgb) -> Self {
+        self.missing = missing;
+        self
+    }
+
+    pub fn with_border(mut self, border: Rgb) -> Self {
+        self.border = border;
+        self
+    }
+
+    /// Draw each cell as a `scale` by `scale` square of pixels
+    pub fn with_scale(mut self, scale: usize) -> Self {
+        self.scale = scale.max(1);
+        self
+    }
+
+    /// States without a colour of their own are drawn like missing cells
+    pub fn color(&self, state: Option<T>) -> Rgb {
+        state.and_then(|state| self.colors.iter().find(|(s, _)| *s == state))
+            .map(|(_, color)| *color)
+            .unwrap_or(self.missing)
+    }
+
+    // every colour the palette can draw, without repeats
+    fn all_colors(&self) -> Vec<Rgb> {
+        let mut all = Vec::new();
+        for color in [self.missing, self.border].iter().chain(self.colors.iter().map(|(_, color)| color)) {
+            if !all.contains(color) {
+                all.push(*color);
+            }
+        }
+        all
+    }
+}
+
+impl Palette<LifeOption> {
+    /// Occupied seats white, empty seats green and floor dark grey
+    pub fn seats() -> Self {
+        Palette::new(&[
+            (LifeOption::Occupied, [255, 255, 255]),
+            (LifeOption::Unoccupied, [40, 160, 40]),
+            (LifeOption::Floor, [32, 32, 32]),
+        ])
+    }
+}
+
+/// An RGB image, stored a row at a time from the top
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    fn new(width: usize, height: usize, fill: Rgb) -> Self {
+        Image{width, height, pixels: fill.iter().copied().cycle().take(width * height * 3).collect()}
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pixel(&self, row: usize, column: usize) -> Rgb {
+        let i = (row * self.width + column) * 3;
+        [self.pixels[i], self.pixels[i + 1], self.pixels[i + 2]]
+    }
+
+    fn fill(&mut self, row: usize, column: usize, height: usize, width: usize, color: Rgb) {
+        for r in row .. row + height {
+            for c in column .. column + width {
+                let i = (r * self.width + c) * 3;
+                self.pixels[i .. i + 3].copy_from_slice(&color);
+            }
+        }
+    }
+
+    /// A binary (P6) PPM file
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut bytes = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        bytes.extend_from_slice(&self.pixels);
+        bytes
+    }
+
+    pub fn to_png(&self) -> Result<Vec<u8>, Error> {
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.write_header()
+            .and_then(|mut writer| {
+                writer.write_image_data(&self.pixels)?;
+                writer.finish()
+            })
+            .map_err(|e| Error::io("Couldn't encode png").with_source(e))?;
+        Ok(bytes)
+    }
+
+    pub fn write_ppm<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        write_file(path.as_ref(), &self.to_ppm())
+    }
+
+    pub fn write_png<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        write_file(path.as_ref(), &self.to_png()?)
+    }
+}
+
+fn write_file(path: &Path, bytes: &[u8]) -> Result<(), Error> {
+    std::fs::write(path, bytes).map_err(|e| Error::io(format!("Couldn't write {}", path.display())).with_source(e))
+}
+
+/// Where each cell of a space goes in an image.  Rows and columns come from the first
+/// two axes; a 3d space is drawn as a row of panels, one per z slice, and in 4d (and up)
+/// each w slice adds another row of panels.  Panels are a cell apart.
+struct Layout<const N: usize> {
+    min: [isize; N],
+    extent: [usize; N],
+    scale: usize,
+}
+
+impl<const N: usize> Layout<N> {
+    fn covering<'a, I: Iterator<Item = &'a Coord<N>>>(coords: I, scale: usize) -> Result<Self, Error> {
+        if N < 2 {
+            return Err(Error::invalid_input("can only draw spaces of 2 or more dimensions"));
+        }
+
+        let mut bounds: Option<([isize; N], [isize; N])> = None;
+        for coord in coords {
+            let (min, max) = bounds.get_or_insert((coord.dim, coord.dim));
+            for i in 0 .. N {
+                min[i] = min[i].min(coord.dim[i]);
+                max[i] = max[i].max(coord.dim[i]);
+            }
+        }
+
+        // an empty space is drawn as a single missing cell
+        let (min, max) = bounds.unwrap_or(([0; N], [0; N]));
+        let mut extent = [0; N];
+        for i in 0 .. N {
+            extent[i] = (max[i] - min[i] + 1) as usize;
+        }
+        Ok(Layout{min, extent, scale})
+    }
+
+    fn panels_across(&self) -> usize {
+        self.extent.get(Z).copied().unwrap_or(1)
+    }
+
+    fn panels_down(&self) -> usize {
+        self.extent.iter().skip(Z + 1).product()
+    }
+
+    /// The panel a coord is drawn in, as (down, across)
+    fn panel(&self, coord: &Coord<N>) -> (usize, usize) {
+        let across = coord.dim.get(Z).map(|z| (z - self.min[Z]) as usize).unwrap_or(0);
+        let down = (Z + 1 .. N).fold(0, |down, i| down * self.extent[i] + (coord.dim[i] - self.min[i]) as usize);
+        (down, across)
+    }
+
+    fn blank(&self, missing: Rgb, border: Rgb) -> Image {
+        let (rows, columns) = (self.extent[X], self.extent[Y]);
+        let height = (self.panels_down() * (rows + 1) - 1) * self.scale;
+        let width = (self.panels_across() * (columns + 1) - 1) * self.scale;
+
+        let mut image = Image::new(width, height, border);
+        for down in 0 .. self.panels_down() {
+            for across in 0 .. self.panels_across() {
+                let row = down * (rows + 1) * self.scale;
+                let column = across * (columns + 1) * self.scale;
+                image.fill(row, column, rows * self.scale, columns * self.scale, missing);
+            }
+        }
+        image
+    }
+
+    fn draw<'a, T: CellState + 'a, I: Iterator<Item = (Coord<N>, &'a T)>>(&self, cells: I, palette: &Palette<T>) -> Image {
+        let mut image = self.blank(palette.missing, palette.border);
+        for (coord, state) in cells {
+            let (down, across) = self.panel(&coord);
+            let row = down * (self.extent[X] + 1) + (coord.dim[X] - self.min[X]) as usize;
+            let column = across * (self.extent[Y] + 1) + (coord.dim[Y] - self.min[Y]) as usize;
+            image.fill(row * self.scale, column * self.scale, self.scale, self.scale, palette.color(Some(*state)));
+        }
+        image
+    }
+}
+
+impl<const N: usize, T: CellState, S: Storage<N, T>> LifeSpace<N, T, S> {
+    /// Draw the space, just big enough to hold every cell in it, with z and w slices as panels.
+    /// Examples:
+    /// ```
+    /// use advent_2020_common::life::{LifeSpace, Palette};
+    /// let input = vec!(String::from("#.L"));
+    /// let space = LifeSpace::<3>::new(&input).unwrap();
+    /// let image = space.render(&Palette::seats().with_scale(2)).unwrap();
+    /// assert_eq!((image.width(), image.height()), (6, 2));
+    /// assert_eq!(image.pixel(1, 0), [255, 255, 255]);
+    /// ```
+    ///
+    pub fn render(&self, palette: &Palette<T>) -> Result<Image, Error> {
+        let coords: Vec<Coord<N>> = Storage::iter(&self.spots).map(|(coord, _)| coord).collect();
+        let layout = Layout::covering(coords.iter(), palette.scale)?;
+        Ok(layout.draw(Storage::iter(&self.spots), palette))
+    }
+
+    /// Like `run`, but adding every generation (this one included) to `recording`
+    pub fn run_recorded<B: Neighbors<N, T>, R: Rule<T>>(&mut self, neighbors: &B, rule: &R, generations: usize, recording: &mut Recording<N, T>) {
+        recording.push(self);
+        for _ in 0 .. generations {
+            self.step(neighbors, rule);
+            recording.push(self);
+        }
+    }
+}
+
+/// The cells of a space over a run of generations, to be drawn as an animation.  Every
+/// frame is drawn at the same size, big enough for the cells of every generation.
+/// Examples:
+/// ```
+/// use advent_2020_common::life::{LifeRule, LifeSpace, Neighborhood, Palette, Recording};
+/// let input = vec!(String::from("###"));
+/// let mut space = LifeSpace::<2>::new(&input).unwrap();
+/// let mut recording = Recording::new();
+/// space.run_recorded(&Neighborhood::moore(1), &LifeRule::conway(), 2, &mut recording);
+///
+/// let frames = recording.render(&Palette::seats().with_scale(1)).unwrap();
+/// assert_eq!(frames.len(), 3);
+/// assert!(frames.iter().all(|frame| frame.width() == 3 && frame.height() == 3));
+/// ```
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recording<const N: usize, T = LifeOption> {
+    frames: Vec<Vec<(Coord<N>, T)>>,
+}
+
+impl<const N: usize, T: CellState> Recording<N, T> {
+    pub fn new() -> Self {
+        Recording{frames: Vec::new()}
+    }
+
+    /// Add the space as it is now as the next frame
+    pub fn push<S: Storage<N, T>>(&mut self, space: &LifeSpace<N, T, S>) {
+        self.frames.push(Storage::iter(&space.spots).map(|(coord, state)| (coord, *state)).collect());
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn render(&self, palette: &Palette<T>) -> Result<Vec<Image>, Error> {
+        let layout = Layout::covering(self.frames.iter().flatten().map(|(coord, _)| coord), palette.scale)?;
+        Ok(self.frames.iter()
+            .map(|frame| layout.draw(frame.iter().map(|(coord, state)| (*coord, state)), palette))
+            .collect())
+    }
+
+    /// Write an animated gif that loops forever, showing each frame for `delay` hundredths of a second
+    pub fn write_gif<P: AsRef<Path>>(&self, path: P, palette: &Palette<T>, delay: u16) -> Result<(), Error> {
+        let path = path.as_ref();
+        let frames = self.render(palette)?;
+        let colors = palette.all_colors();
+        if colors.len() > 256 {
+            return Err(Error::invalid_input("a gif can't have more than 256 colours"));
+        }
+        let (width, height) = frames.first().map(|frame| (frame.width, frame.height)).unwrap_or((1, 1));
+        if width > u16::MAX as usize || height > u16::MAX as usize {
+            return Err(Error::invalid_input(format!("{}x{} is too big for a gif", width, height)));
+        }
+
+        let index: HashMap<Rgb, u8> = colors.iter().enumerate().map(|(i, color)| (*color, i as u8)).collect();
+        let global_palette: Vec<u8> = colors.iter().flatten().copied().collect();
+        let gif_error = |e: gif::EncodingError| Error::io(format!("Couldn't write {}", path.display())).with_source(e);
+
+        let file = std::fs::File::create(path)
+            .map_err(|e| Error::io(format!("Couldn't create {}", path.display())).with_source(e))?;
+        let mut encoder = gif::Encoder::new(file, width as u16, height as u16, &global_palette).map_err(gif_error)?;
+        encoder.set_repeat(gif::Repeat::Infinite).map_err(gif_error)?;
+        for image in frames.iter() {
+            let buffer: Vec<u8> = image.pixels.chunks_exact(3).map(|rgb| index[&[rgb[0], rgb[1], rgb[2]]]).collect();
+            let frame = gif::Frame{width: width as u16, height: height as u16, delay, buffer: Cow::Owned(buffer), ..gif::Frame::default()};
+            encoder.write_frame(&frame).map_err(gif_error)?;
+        }
+        Ok(())
+    }
+
+    /// Write each frame to `dir` as a png, numbered from `frame_0000.png`, returning their paths
+    pub fn write_frames<P: AsRef<Path>>(&self, dir: P, palette: &Palette<T>) -> Result<Vec<PathBuf>, Error> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)
+            .map_err(|e| Error::io(format!("Couldn't create {}", dir.display())).with_source(e))?;
+
+        let digits = self.frames.len().saturating_sub(1).to_string().len().max(4);
+        let mut paths = Vec::new();
+        for (i, image) in self.render(palette)?.iter().enumerate() {
+            let path = dir.join(format!("frame_{:0width$}.png", i, width = digits));
+            image.write_png(&path)?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+}
+
+impl<const N: usize, T: CellState> Default for Recording<N, T> {
+    fn default() -> Self {
+        Recording::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::life::{LifeRule, Neighborhood};
+
+    const WHITE: Rgb = [255, 255, 255];
+    const GREEN: Rgb = [40, 160, 40];
+    const GREY: Rgb = [32, 32, 32];
+    const BLACK: Rgb = [0, 0, 0];
+    const BORDER: Rgb = [128, 128, 128];
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("advent_2020_image_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_render_2d() {
+        let input = vec!(String::from("#."), String::from(".L"));
+        let space = LifeSpace::<2>::new(&input).unwrap();
+        let image = space.render(&Palette::seats().with_scale(1)).unwrap();
+        assert_eq!((image.width(), image.height()), (2, 2));
+        assert_eq!(image.pixel(0, 0), WHITE);
+        assert_eq!(image.pixel(0, 1), GREY);
+        assert_eq!(image.pixel(1, 1), GREEN);
+    }
+
+    #[test]
+    fn test_render_panels() {
+        // two z slices of 2x1, and two w slices: 2x2 panels of 2 rows by 1 column
+        let mut space = LifeSpace::<4>::new(&[String::from("#"), String::from("L")]).unwrap();
+        space.spots.insert(Coord::new_4d(0, 0, 1, 1), LifeOption::Floor);
+        let image = space.render(&Palette::seats().with_scale(1)).unwrap();
+        assert_eq!((image.width(), image.height()), (3, 5));
+
+        let rows: Vec<Vec<Rgb>> = (0 .. 5).map(|r| (0 .. 3).map(|c| image.pixel(r, c)).collect()).collect();
+        assert_eq!(rows, vec!(
+            vec!(WHITE, BORDER, BLACK),
+            vec!(GREEN, BORDER, BLACK),
+            vec!(BORDER, BORDER, BORDER),
+            vec!(BLACK, BORDER, GREY),
+            vec!(BLACK, BORDER, BLACK),
+        ));
+    }
+
+    #[test]
+    fn test_ppm_and_png() {
+        let space = LifeSpace::<2>::new(&[String::from("#L")]).unwrap();
+        let image = space.render(&Palette::seats().with_scale(3)).unwrap();
+
+        let ppm = image.to_ppm();
+        assert!(ppm.starts_with(b"P6\n6 3\n255\n"));
+        assert_eq!(ppm.len(), 11 + 6 * 3 * 3);
+
+        let png = image.to_png().unwrap();
+        let mut reader = png::Decoder::new(png.as_slice()).read_info().unwrap();
+        let mut pixels = vec!(0; reader.output_buffer_size());
+        let info = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!((info.width, info.height), (6, 3));
+        assert_eq!(&pixels[.. info.buffer_size()], image.pixels.as_slice());
+    }
+
+    #[test]
+    fn test_write_animation() {
+        let dir = temp_dir("animation");
+        let mut space = LifeSpace::<2>::new(&[String::from("###")]).unwrap();
+        let mut recording = Recording::new();
+        space.run_recorded(&Neighborhood::moore(1), &LifeRule::conway(), 3, &mut recording);
+        assert_eq!(recording.len(), 4);
+
+        let palette = Palette::seats().with_scale(2);
+        let paths = recording.write_frames(&dir, &palette).unwrap();
+        assert_eq!(paths.last().unwrap(), &dir.join("frame_0003.png"));
+        assert!(paths.iter().all(|path| path.exists()));
+
+        let gif_path = dir.join("blinker.gif");
+        recording.write_gif(&gif_path, &palette, 20).unwrap();
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::RGBA);
+        let mut decoder = options.read_info(std::fs::File::open(&gif_path).unwrap()).unwrap();
+        let mut frames = 0;
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!((frame.width, frame.height, frame.delay), (6, 6, 20));
+            frames += 1;
+        }
+        assert_eq!(frames, 4);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
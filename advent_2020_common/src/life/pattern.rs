@@ -0,0 +1,310 @@
+use super::{Coord, LifeOption, LifeRule, LifeSpace, Storage, X, Y};
+use crate::Error;
+use std::collections::HashSet;
+
+/// Longest line `to_rle` writes, as the format asks
+const RLE_LINE_LENGTH: usize = 70;
+
+/// Life patterns in the formats they're shared in: run length encoded (`.rle`), and
+/// plaintext (`.cells`).  Occupied cells are alive; reading a pattern only adds its live
+/// cells, with its top left corner at row 0, column 0, and writing one only looks at the
+/// box around the live cells.
+impl<S: Storage<2>> LifeSpace<2, LifeOption, S> {
+    /// Read a run length encoded pattern and the rule from its header, B3/S23 if it doesn't say.
+    /// Examples:
+    /// ```
+    /// use advent_2020_common::life::{LifeOption, LifeRule, LifeSpace};
+    /// let glider = "#N Glider\nx = 3, y = 3, rule = B3/S23\nbob$2bo$3o!";
+    /// let (space, rule) = LifeSpace::<2>::from_rle(glider).unwrap();
+    /// assert_eq!(rule, LifeRule::conway());
+    /// assert_eq!(space.count(LifeOption::Occupied), 5);
+    /// assert_eq!(space.at_2d(0, 1), Some(&LifeOption::Occupied));
+    /// assert_eq!(space.to_rle(&rule), "x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n");
+    /// ```
+    ///
+    pub fn from_rle(input: &str) -> Result<(Self, LifeRule), Error> {
+        let mut space = Self::empty();
+        let (mut row, mut column) = (0usize, 0usize);
+        let mut count: Option<usize> = None;
+
+        let mut lines = input.lines().enumerate().filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'));
+        let (width, height, rule) = match lines.next() {
+            Some((i, header)) => parse_rle_header(header).map_err(|e| e.at_line(i + 1))?,
+            None => return Err(Error::parse("rle has no header")),
+        };
+
+        'lines: for (i, line) in lines {
+            for (j, c) in line.chars().enumerate() {
+                let at = |e: Error| e.at(i + 1, j + 1);
+                if let Some(digit) = c.to_digit(10) {
+                    count = Some(count.unwrap_or(0).checked_mul(10).and_then(|n| n.checked_add(digit as usize))
+                        .ok_or_else(|| at(Error::parse("rle run count is too big")))?);
+                    continue;
+                }
+                // runs can't go past the size in the header, so a bad count can't make us
+                // insert more cells than the pattern has room for
+                let run = count.take().unwrap_or(1);
+                let past_width = || at(Error::parse(format!("rle run goes past the pattern's width {}", width)));
+                match c {
+                    'b' | '.' => column = column.checked_add(run).filter(|end| *end <= width).ok_or_else(past_width)?,
+                    'o' => {
+                        let end = column.checked_add(run).filter(|end| *end <= width).ok_or_else(past_width)?;
+                        if row >= height {
+                            return Err(at(Error::parse(format!("rle row {} is past the pattern's height {}", row + 1, height))));
+                        }
+                        for c in column .. end {
+                            space.spots.insert(Coord::new_2d(row as isize, c as isize), LifeOption::Occupied);
+                        }
+                        column = end;
+                    },
+                    '$' => {
+                        row = row.checked_add(run).filter(|end| *end <= height)
+                            .ok_or_else(|| at(Error::parse(format!("rle run goes past the pattern's height {}", height))))?;
+                        column = 0;
+                    },
+                    '!' => break 'lines,
+                    c if c.is_whitespace() => (),
+                    c => return Err(Error::parse(format!("unknown rle tag {}", c)).at(i + 1, j + 1)),
+                }
+            }
+        }
+
+        Ok((space, rule.unwrap_or_else(LifeRule::conway)))
+    }
+
+    /// Write the live cells as a run length encoded pattern, with `rule` in the header
+    pub fn to_rle(&self, rule: &LifeRule) -> String {
+        let live = self.live_cells();
+        let ((top, left), (bottom, right)) = bounds(&live);
+        let mut result = format!("x = {}, y = {}, rule = {}\n", right + 1 - left, bottom + 1 - top, rule);
+
+        // runs of each tag, with trailing dead cells and empty rows left implicit
+        let mut runs: Vec<(usize, char)> = Vec::new();
+        let mut push = |tag: char| match runs.last_mut() {
+            Some((count, last)) if *last == tag => *count += 1,
+            _ => runs.push((1, tag)),
+        };
+        for row in top ..= bottom {
+            if row > top {
+                push('$');
+            }
+            if let Some(last) = (left ..= right).rev().find(|c| live.contains(&(row, *c))) {
+                for column in left ..= last {
+                    push(if live.contains(&(row, column)) { 'o' } else { 'b' });
+                }
+            }
+        }
+
+        let mut line = String::new();
+        for (count, tag) in runs.iter() {
+            let token = if *count == 1 { tag.to_string() } else { format!("{}{}", count, tag) };
+            if line.len() + token.len() >= RLE_LINE_LENGTH {
+                result.push_str(&line);
+                result.push('\n');
+                line.clear();
+            }
+            line.push_str(&token);
+        }
+        result.push_str(&line);
+        result.push_str("!\n");
+        result
+    }
+
+    /// Read a plaintext pattern: `O` (or `*`) for a live cell, `.` for a dead one, and `!` comments.
+    /// Examples:
+    /// ```
+    /// use advent_2020_common::life::{LifeOption, LifeSpace};
+    /// let blinker = "!Name: Blinker\n.O\n.O\n.O\n";
+    /// let space = LifeSpace::<2>::from_cells(blinker).unwrap();
+    /// assert_eq!(space.count(LifeOption::Occupied), 3);
+    /// assert_eq!(space.to_cells(), "O\nO\nO\n");
+    /// ```
+    ///
+    pub fn from_cells(input: &str) -> Result<Self, Error> {
+        let mut space = Self::empty();
+        let rows = input.lines().enumerate().filter(|(_, line)| !line.starts_with('!'));
+        for (row, (i, line)) in rows.enumerate() {
+            for (column, c) in line.trim_end().chars().enumerate() {
+                match c {
+                    'O' | '*' => space.spots.insert(Coord::new_2d(row as isize, column as isize), LifeOption::Occupied),
+                    '.' => (),
+                    c => return Err(Error::parse(format!("unknown cell {}", c)).at(i + 1, column + 1)),
+                }
+            }
+        }
+        Ok(space)
+    }
+
+    /// Write the live cells as a plaintext pattern, leaving off dead cells at the ends of rows
+    pub fn to_cells(&self) -> String {
+        let live = self.live_cells();
+        let ((top, left), (bottom, right)) = bounds(&live);
+        let mut result = String::new();
+        for row in top ..= bottom {
+            let last = (left ..= right).rev().find(|c| live.contains(&(row, *c))).unwrap_or(left - 1);
+            result.extend((left ..= last).map(|column| if live.contains(&(row, column)) { 'O' } else { '.' }));
+            result.push('\n');
+        }
+        result
+    }
+
+    fn live_cells(&self) -> HashSet<(isize, isize)> {
+        Storage::iter(&self.spots)
+            .filter(|(_, state)| **state == LifeOption::Occupied)
+            .map(|(coord, _)| (coord.at(X), coord.at(Y)))
+            .collect()
+    }
+}
+
+// top left and bottom right of the box around `cells`; an empty box for no cells
+fn bounds(cells: &HashSet<(isize, isize)>) -> ((isize, isize), (isize, isize)) {
+    if cells.is_empty() {
+        return ((0, 0), (-1, -1));
+    }
+    let top = cells.iter().map(|c| c.0).min().unwrap();
+    let bottom = cells.iter().map(|c| c.0).max().unwrap();
+    let left = cells.iter().map(|c| c.1).min().unwrap();
+    let right = cells.iter().map(|c| c.1).max().unwrap();
+    ((top, left), (bottom, right))
+}
+
+// `x = 3, y = 3, rule = B3/S23`: the width and height the body has to fit in, and the rule
+// if there is one
+fn parse_rle_header(header: &str) -> Result<(usize, usize, Option<LifeRule>), Error> {
+    let (mut width, mut height, mut rule) = (None, None, None);
+    for field in header.split(',') {
+        let (key, value) = match field.split_once('=') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => return Err(Error::parse(format!("bad rle header field {}", field.trim()))),
+        };
+        match key {
+            "x" | "y" => {
+                let size = value.parse::<usize>().map_err(|e| Error::parse(format!("bad rle size {}", value)).with_source(e))?;
+                if key == "x" { width = Some(size) } else { height = Some(size) }
+            },
+            "rule" => rule = Some(parse_rle_rule(value)?),
+            _ => return Err(Error::parse(format!("unknown rle header field {}", key))),
+        }
+    }
+    match (width, height) {
+        (Some(width), Some(height)) => Ok((width, height, rule)),
+        _ => Err(Error::parse("rle header has no size")),
+    }
+}
+
+// either B3/S23, or the older survival / birth form 23/3
+fn parse_rle_rule(rule: &str) -> Result<LifeRule, Error> {
+    let counts = |part: &str| part.chars().map(|c| c.to_digit(10).map(|d| d as usize)).collect::<Option<Vec<usize>>>();
+    match rule.split_once('/') {
+        Some((survival, birth)) if !rule.contains(|c: char| c.is_alphabetic()) => {
+            match (counts(survival), counts(birth)) {
+                (Some(survival), Some(birth)) => Ok(LifeRule{birth, survival}),
+                _ => Err(Error::parse(format!("bad rule {}", rule))),
+            }
+        },
+        _ => LifeRule::parse(rule),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::life::Neighborhood;
+
+    fn gosper_gun() -> &'static str {
+        "#N Gosper glider gun\n\
+         #C This was the first gun discovered.\n\
+         x = 36, y = 9, rule = B3/S23\n\
+         24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$2o8bo3bob2o4b\n\
+         obo$10bo5bo7bo$11bo3bo$12b2o!\n"
+    }
+
+    fn occupied(space: &LifeSpace<2>) -> Vec<Coord<2>> {
+        let mut cells: Vec<Coord<2>> = space.spots.iter()
+            .filter(|(_, state)| **state == LifeOption::Occupied)
+            .map(|(coord, _)| *coord)
+            .collect();
+        cells.sort();
+        cells
+    }
+
+    #[test]
+    fn test_rle_round_trip() {
+        let (gun, rule) = LifeSpace::<2>::from_rle(gosper_gun()).unwrap();
+        assert_eq!(gun.count(LifeOption::Occupied), 36);
+        assert_eq!(gun.at_2d(4, 0), Some(&LifeOption::Occupied));
+        assert_eq!(gun.at_2d(0, 24), Some(&LifeOption::Occupied));
+
+        let written = gun.to_rle(&rule);
+        assert!(written.lines().all(|line| line.len() <= RLE_LINE_LENGTH));
+        assert!(written.starts_with("x = 36, y = 9, rule = B3/S23\n24bo$22bobo$12b2o6b2o12b2o$"));
+        let (read_back, read_rule) = LifeSpace::<2>::from_rle(&written).unwrap();
+        assert_eq!(occupied(&read_back), occupied(&gun));
+        assert_eq!(read_rule, rule);
+    }
+
+    #[test]
+    fn test_rle_rules() {
+        let (_, rule) = LifeSpace::<2>::from_rle("x = 1, y = 1\no!").unwrap();
+        assert_eq!(rule, LifeRule::conway());
+        let (_, rule) = LifeSpace::<2>::from_rle("x = 1, y = 1, rule = 23/36\no!").unwrap();
+        assert_eq!(rule, LifeRule::parse("B36/S23").unwrap());
+
+        // whatever the simulation runs, the header carries through; the blinker turns upright,
+        // and comes back with its corner at the origin
+        let highlife = LifeRule::parse("B36/S23").unwrap();
+        let (mut space, _) = LifeSpace::<2>::from_rle("x = 3, y = 1\n3o!").unwrap();
+        space.run(&Neighborhood::moore(1), &highlife, 3);
+        let (stepped, rule) = LifeSpace::<2>::from_rle(&space.to_rle(&highlife)).unwrap();
+        assert_eq!(rule, highlife);
+        assert_eq!(occupied(&stepped), vec!(Coord::new_2d(0, 0), Coord::new_2d(1, 0), Coord::new_2d(2, 0)));
+    }
+
+    #[test]
+    fn test_rle_empty_rows() {
+        let (space, _) = LifeSpace::<2>::from_rle("x = 2, y = 4\no3$bo!").unwrap();
+        assert_eq!(occupied(&space), vec!(Coord::new_2d(0, 0), Coord::new_2d(3, 1)));
+        assert_eq!(space.to_rle(&LifeRule::conway()), "x = 2, y = 4, rule = B3/S23\no3$bo!\n");
+        assert_eq!(LifeSpace::<2>::empty().to_rle(&LifeRule::conway()), "x = 0, y = 0, rule = B3/S23\n!\n");
+    }
+
+    #[test]
+    fn test_rle_errors() {
+        assert!(LifeSpace::<2>::from_rle("").is_err());
+        assert!(LifeSpace::<2>::from_rle("x = 3, y = three\no!").is_err());
+        assert!(LifeSpace::<2>::from_rle("x = 3, y = 3, rule = B3/Sx\no!").is_err());
+        let err = LifeSpace::<2>::from_rle("x = 3, y = 3\nbo$\n2bq!").err().unwrap();
+        assert_eq!(err.to_string(), "unknown rle tag q (line 3, column 3)");
+        assert!(LifeSpace::<2>::from_rle("rule = B3/S23\no!").is_err());
+
+        // run counts that overflow, or go past the size in the header
+        let err = LifeSpace::<2>::from_rle("x = 1, y = 1\n99999999999999999999999o!").err().unwrap();
+        assert_eq!(err.to_string(), "rle run count is too big (line 2, column 20)");
+        let err = LifeSpace::<2>::from_rle("x = 3, y = 2\nbo$\nb3o!").err().unwrap();
+        assert_eq!(err.to_string(), "rle run goes past the pattern's width 3 (line 3, column 3)");
+        let err = LifeSpace::<2>::from_rle("x = 3, y = 2\nbo$\n1000000000000bo!").err().unwrap();
+        assert_eq!(err.to_string(), "rle run goes past the pattern's width 3 (line 3, column 14)");
+        let err = LifeSpace::<2>::from_rle("x = 3, y = 2\nbo2$o!").err().unwrap();
+        assert_eq!(err.to_string(), "rle row 3 is past the pattern's height 2 (line 2, column 5)");
+        assert!(LifeSpace::<2>::from_rle("x = 3, y = 2\nbo3$!").is_err());
+        // a trailing $ that only ends the last row is fine
+        assert!(LifeSpace::<2>::from_rle("x = 3, y = 2\nbo$3o$!").is_ok());
+    }
+
+    #[test]
+    fn test_cells() {
+        let glider = "!Name: Glider\n!\n.O\n..O\nOOO\n";
+        let space = LifeSpace::<2>::from_cells(glider).unwrap();
+        assert_eq!(occupied(&space), vec!(
+            Coord::new_2d(0, 1), Coord::new_2d(1, 2), Coord::new_2d(2, 0), Coord::new_2d(2, 1), Coord::new_2d(2, 2),
+        ));
+        assert_eq!(space.to_cells(), ".O\n..O\nOOO\n");
+
+        let (gun, _) = LifeSpace::<2>::from_rle(gosper_gun()).unwrap();
+        let from_cells = LifeSpace::<2>::from_cells(&gun.to_cells()).unwrap();
+        assert_eq!(occupied(&from_cells), occupied(&gun));
+
+        let err = LifeSpace::<2>::from_cells("!comment\n.O\n.#").err().unwrap();
+        assert_eq!(err.to_string(), "unknown cell # (line 3, column 2)");
+    }
+}
@@ -0,0 +1,256 @@
+use super::{CellState, Coord, LifeOption, LifeSpace, Neighborhood, Neighbors, Storage};
+use std::collections::HashMap;
+
+/// Neighbours found by looking out from a cell in each of a set of directions, past any
+/// cells in a transparent state, to the first cell that isn't: day 11's "first seat you
+/// can see".  Rays follow the space's topology, and end with nothing in sight when they
+/// leave the space, come back round to where they started, or go further than the
+/// maximum distance.
+///
+/// Walking rays is slow, so `precompute` works out what every cell in a space can see
+/// once, and stepping reuses that.  The lists assume cells never change between being
+/// transparent and not, as with day 11's floor; cells without a list walk their rays.
+/// Examples:
+/// ```
+/// use advent_2020_common::life::{Coord, LifeOption, LifeSpace, LineOfSight};
+/// let input = vec!(String::from("#..L..#"));
+/// let space = LifeSpace::<2>::new(&input).unwrap();
+/// let sight = LineOfSight::new(&[LifeOption::Floor]).precompute(&space);
+/// assert_eq!(sight.visible(&space, &Coord::new_2d(0, 3)), vec!(Coord::new_2d(0, 0), Coord::new_2d(0, 6)));
+///
+/// let short_sighted = LineOfSight::new(&[LifeOption::Floor]).with_max_distance(2);
+/// assert_eq!(short_sighted.visible(&space, &Coord::new_2d(0, 3)), vec!());
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct LineOfSight<const N: usize, T = LifeOption> {
+    directions: Neighborhood<N>,
+    transparent: Vec<T>,
+    max_distance: Option<usize>,
+    visible: HashMap<Coord<N>, Vec<Coord<N>>>,
+}
+
+impl<const N: usize, T: CellState> LineOfSight<N, T> {
+    /// Look past cells in any of the `transparent` states, along every axis and diagonal
+    /// (the directions of a radius 1 Moore neighbourhood), as far as the space goes
+    pub fn new(transparent: &[T]) -> Self {
+        LineOfSight{
+            directions: Neighborhood::moore(1),
+            transparent: transparent.to_vec(),
+            max_distance: None,
+            visible: HashMap::new(),
+        }
+    }
+
+    /// Look along each of the offsets in `directions` instead
+    pub fn with_directions(mut self, directions: Neighborhood<N>) -> Self {
+        self.directions = directions;
+        self.visible.clear();
+        self
+    }
+
+    /// See no further than `distance` steps along each direction
+    pub fn with_max_distance(mut self, distance: usize) -> Self {
+        self.max_distance = Some(distance);
+        self.visible.clear();
+        self
+    }
+
+    /// Work out what every cell in `space` can see, so stepping `space` doesn't have to
+    pub fn precompute<S: Storage<N, T>>(mut self, space: &LifeSpace<N, T, S>) -> Self {
+        self.visible = Storage::iter(&space.spots)
+            .map(|(coord, _)| (coord, self.look(space, &coord)))
+            .collect();
+        self
+    }
+
+    /// The cells `coord` can see in `space`, in the order of the directions
+    pub fn visible<S: Storage<N, T>>(&self, space: &LifeSpace<N, T, S>, coord: &Coord<N>) -> Vec<Coord<N>> {
+        match self.visible.get(coord) {
+            Some(visible) => visible.clone(),
+            None => self.look(space, coord),
+        }
+    }
+
+    fn look<S: Storage<N, T>>(&self, space: &LifeSpace<N, T, S>, coord: &Coord<N>) -> Vec<Coord<N>> {
+        self.directions.offsets().iter()
+            .filter_map(|direction| self.first_visible(space, coord, direction))
+            .collect()
+    }
+
+    fn first_visible<S: Storage<N, T>>(&self, space: &LifeSpace<N, T, S>, from: &Coord<N>, direction: &[isize; N]) -> Option<Coord<N>> {
+        // the ray goes on in a straight line, and each cell it passes is wherever the
+        // topology puts that: stepping on from where it was resolved to would bounce
+        // forever off a mirror's edge, which resolves back onto itself
+        let mut ray = *from;
+        let mut distance = 0;
+        loop {
+            distance += 1;
+            if self.max_distance.is_some_and(|max| distance > max) {
+                return None;
+            }
+
+            ray = ray.offset(direction);
+            let seen = space.topology.resolve(&ray)?;
+            if seen == *from {
+                return None;
+            }
+            match space.spots.get(&seen) {
+                Some(state) if self.transparent.contains(state) => (),
+                Some(_) => return Some(seen),
+                None => return None,
+            }
+        }
+    }
+}
+
+impl<const N: usize, T: CellState> Neighbors<N, T> for LineOfSight<N, T> {
+    fn visit<S: Storage<N, T>>(&self, space: &LifeSpace<N, T, S>, coord: &Coord<N>, visit: &mut dyn FnMut(Coord<N>)) {
+        match self.visible.get(coord) {
+            Some(visible) => visible.iter().for_each(|c| visit(*c)),
+            None => self.look(space, coord).into_iter().for_each(visit),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::life::{LifeRule, Topology};
+
+    fn seats() -> Vec<String> {
+        vec!(
+            String::from(".......#."),
+            String::from("...#....."),
+            String::from(".#......."),
+            String::from("........."),
+            String::from("..#L....#"),
+            String::from("....#...."),
+            String::from("........."),
+            String::from("#........"),
+            String::from("...#....."),
+        )
+    }
+
+    #[test]
+    fn test_matches_skip_floor() {
+        let space = LifeSpace::<2>::new(&seats()).unwrap();
+        let sight = LineOfSight::new(&[LifeOption::Floor]);
+        let precomputed = sight.clone().precompute(&space);
+
+        for (coord, _) in space.spots.iter() {
+            let expected: Vec<LifeOption> = space.neighbors_skip_floor(coord.at(0), coord.at(1)).collect();
+            for visible in [sight.visible(&space, coord), precomputed.visible(&space, coord)].iter() {
+                let states: Vec<LifeOption> = visible.iter().map(|c| *space.at(c).unwrap()).collect();
+                assert_eq!(states, expected, "at {:?}", coord);
+            }
+        }
+        assert_eq!(precomputed.visible(&space, &Coord::new_2d(4, 3)).len(), 8);
+    }
+
+    #[test]
+    fn test_transparent_states() {
+        // looking through occupied seats as well as floor, only empty seats are in sight
+        let input = vec!(String::from("L.#.L#L"));
+        let space = LifeSpace::<2>::new(&input).unwrap();
+        let sight = LineOfSight::new(&[LifeOption::Floor, LifeOption::Occupied]);
+        assert_eq!(sight.visible(&space, &Coord::new_2d(0, 4)), vec!(Coord::new_2d(0, 0), Coord::new_2d(0, 6)));
+        assert_eq!(sight.visible(&space, &Coord::new_2d(0, 0)), vec!(Coord::new_2d(0, 4)));
+    }
+
+    #[test]
+    fn test_max_distance() {
+        let input = vec!(String::from("#...#"), String::from("....."), String::from("..L.."));
+        let space = LifeSpace::<2>::new(&input).unwrap();
+        let far = LineOfSight::new(&[LifeOption::Floor]).precompute(&space);
+        let near = LineOfSight::new(&[LifeOption::Floor]).with_max_distance(1).precompute(&space);
+        assert_eq!(far.visible(&space, &Coord::new_2d(2, 2)), vec!(Coord::new_2d(0, 0), Coord::new_2d(0, 4)));
+        assert_eq!(near.visible(&space, &Coord::new_2d(2, 2)), vec!());
+        assert_eq!(near.with_max_distance(2).visible(&space, &Coord::new_2d(2, 2)).len(), 2);
+    }
+
+    #[test]
+    fn test_3d() {
+        // a 3x3x4 box of floor, with a seat in the middle of the bottom layer and two on top
+        let input = vec!(String::from("..."), String::from(".L."), String::from("..."));
+        let mut space = LifeSpace::<3>::new(&input).unwrap();
+        for z in 1 ..= 3 {
+            for row in 0 .. 3 {
+                for column in 0 .. 3 {
+                    space.spots.insert(Coord::new_3d(row, column, z), LifeOption::Floor);
+                }
+            }
+        }
+        space.spots.insert(Coord::new_3d(2, 2, 3), LifeOption::Occupied);
+        space.spots.insert(Coord::new_3d(1, 1, 3), LifeOption::Occupied);
+
+        let sight = LineOfSight::new(&[LifeOption::Floor]).precompute(&space);
+        assert_eq!(sight.visible(&space, &Coord::new_3d(1, 1, 0)), vec!(Coord::new_3d(1, 1, 3)));
+        assert_eq!(sight.visible(&space, &Coord::new_3d(1, 1, 3)), vec!(Coord::new_3d(1, 1, 0), Coord::new_3d(2, 2, 3)));
+
+        let rook = LineOfSight::new(&[LifeOption::Floor]).with_directions(Neighborhood::von_neumann(1));
+        assert_eq!(rook.visible(&space, &Coord::new_3d(1, 1, 3)), vec!(Coord::new_3d(1, 1, 0)));
+    }
+
+    #[test]
+    fn test_topology_without_precompute() {
+        // rays walked on demand go round the torus just as the precomputed ones do
+        let input = vec!(String::from("L..."), String::from("..L."), String::from(".#.."));
+        let torus = Topology::torus(Coord::new_2d(0, 0), Coord::new_2d(2, 3));
+        let space = LifeSpace::<2>::new(&input).unwrap().with_topology(torus);
+        let sight = LineOfSight::new(&[LifeOption::Floor]);
+        let precomputed = sight.clone().precompute(&space);
+        for (coord, _) in space.spots.iter() {
+            assert_eq!(sight.visible(&space, coord), precomputed.visible(&space, coord), "at {:?}", coord);
+        }
+        // from (1, 2) the straight rays come back round to it; two diagonals wrap round to
+        // each of the other seats, where without the torus only one is in sight
+        let mut seen = sight.visible(&space, &Coord::new_2d(1, 2));
+        seen.sort();
+        assert_eq!(seen, vec!(Coord::new_2d(0, 0), Coord::new_2d(0, 0), Coord::new_2d(2, 1), Coord::new_2d(2, 1)));
+        let unbounded = LifeSpace::<2>::new(&input).unwrap();
+        assert_eq!(sight.visible(&unbounded, &Coord::new_2d(1, 2)), vec!(Coord::new_2d(2, 1)));
+
+        let mut walked = space.clone();
+        let mut cached = space.clone();
+        walked.step(&sight, &LifeRule::parse("B0123/S").unwrap());
+        cached.step(&precomputed, &LifeRule::parse("B0123/S").unwrap());
+        assert_eq!(walked.to_string().unwrap(), cached.to_string().unwrap());
+    }
+
+    #[test]
+    fn test_torus() {
+        // round the torus, the seat sees itself in every direction, which doesn't count
+        let input = vec!(String::from("L.."), String::from("..."));
+        let torus = Topology::torus(Coord::new_2d(0, 0), Coord::new_2d(1, 2));
+        let space = LifeSpace::<2>::new(&input).unwrap().with_topology(torus);
+        let sight = LineOfSight::new(&[LifeOption::Floor]).precompute(&space);
+        assert_eq!(sight.visible(&space, &Coord::new_2d(0, 0)), vec!());
+        // the four diagonals all reach it eventually; straight along an axis just comes back
+        assert_eq!(sight.visible(&space, &Coord::new_2d(1, 1)), vec!(Coord::new_2d(0, 0); 4));
+    }
+
+    #[test]
+    fn test_mirror() {
+        // a ray reflects off the edge, seeing the edge cell twice, then heads back
+        let input = vec!(String::from("L.."));
+        let mirror = Topology::mirror(Coord::new_2d(0, 0), Coord::new_2d(0, 2));
+        let space = LifeSpace::<2>::new(&input).unwrap().with_topology(mirror);
+        let sight = LineOfSight::new(&[LifeOption::Floor]);
+        let precomputed = sight.clone().precompute(&space);
+        for (coord, _) in space.spots.iter() {
+            assert_eq!(sight.visible(&space, coord), precomputed.visible(&space, coord), "at {:?}", coord);
+        }
+        // every ray from the seat comes back to it
+        assert_eq!(sight.visible(&space, &Coord::new_2d(0, 0)), vec!());
+        // the rays heading left see the seat; the others are reflected back before getting past
+        assert_eq!(sight.visible(&space, &Coord::new_2d(0, 1)), vec!(Coord::new_2d(0, 0); 3));
+        assert_eq!(sight.visible(&space, &Coord::new_2d(0, 2)), vec!(Coord::new_2d(0, 0); 3));
+
+        let mut walked = space.clone();
+        let mut cached = space.clone();
+        walked.step(&sight, &LifeRule::parse("B0123/S").unwrap());
+        cached.step(&precomputed, &LifeRule::parse("B0123/S").unwrap());
+        assert_eq!(walked.to_string().unwrap(), cached.to_string().unwrap());
+    }
+}
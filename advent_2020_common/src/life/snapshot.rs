@@ -0,0 +1,535 @@
+use super::{Boundary, CellState, CharMap, Coord, LifeSpace, Storage, Topology};
+use crate::Error;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::convert::{TryFrom, TryInto};
+use std::path::Path;
+
+/// Starts every binary snapshot, followed by the format version
+const MAGIC: &[u8; 4] = b"LIFE";
+const VERSION: u8 = 1;
+
+/// Storage is only sized up front for the box around a snapshot's cells if they fill at
+/// least this fraction of it, and dense storage isn't loaded at all otherwise; a sparse
+/// space's box can be far bigger than its cells need
+const MAX_SPARSENESS: usize = 16;
+
+/// Everything needed to pick a space back up where it left off.  Cells are grouped by the
+/// character their state is written as, so any state with a `CharMap` entry can be saved.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Snapshot {
+    dimensions: usize,
+    generation: usize,
+    min: Vec<isize>,
+    max: Vec<isize>,
+    topology: Vec<Boundary>,
+    cells: BTreeMap<char, Vec<Vec<isize>>>,
+}
+
+/// Saving a space part way through a run, and resuming it later.  Snapshots hold the
+/// dimensionality, the bounds of the stored cells, every cell's state, the topology and
+/// the generation, so a loaded space steps on exactly as the saved one would have.
+/// There are two forms: JSON to read, and a compact binary one to keep.
+impl<const N: usize, T: CellState, S: Storage<N, T>> LifeSpace<N, T, S> {
+    /// The space as pretty printed JSON, with states written as their characters in `chars`
+    /// Examples:
+    /// ```
+    /// use advent_2020_common::life::{CharMap, LifeRule, LifeSpace, Neighborhood};
+    /// let input = vec!(String::from(".#."), String::from("..#"), String::from("###"));
+    /// let mut space = LifeSpace::<3>::new(&input).unwrap();
+    /// space.run(&Neighborhood::moore(1), &LifeRule::conway(), 2);
+    ///
+    /// let json = space.to_json(&CharMap::seats()).unwrap();
+    /// let resumed = LifeSpace::<3>::from_json(&json, &CharMap::seats()).unwrap();
+    /// assert_eq!(resumed.generation, 2);
+    /// assert_eq!(resumed.to_string().unwrap(), space.to_string().unwrap());
+    /// assert!(LifeSpace::<4>::from_json(&json, &CharMap::seats()).is_err());
+    /// ```
+    ///
+    pub fn to_json(&self, chars: &CharMap<T>) -> Result<String, Error> {
+        serde_json::to_string_pretty(&self.snapshot(chars)?)
+            .map_err(|e| Error::invalid_input("Couldn't write snapshot").with_source(e))
+    }
+
+    pub fn from_json(json: &str, chars: &CharMap<T>) -> Result<Self, Error> {
+        let snapshot: Snapshot = serde_json::from_str(json)
+            .map_err(|e| Error::parse("Couldn't read snapshot").at(e.line(), e.column()).with_source(e))?;
+        Self::restore(snapshot, chars)
+    }
+
+    /// The space as a binary snapshot: a `LIFE` header, then variable length integers, with
+    /// each cell's coords counted from the bottom corner of the bounds
+    pub fn to_bytes(&self, chars: &CharMap<T>) -> Result<Vec<u8>, Error> {
+        Ok(encode(&self.snapshot(chars)?))
+    }
+
+    pub fn from_bytes(bytes: &[u8], chars: &CharMap<T>) -> Result<Self, Error> {
+        Self::restore(decode(bytes, N)?, chars)
+    }
+
+    /// Save a snapshot to `path`, as JSON if it ends in `.json` and binary otherwise
+    pub fn save_snapshot<P: AsRef<Path>>(&self, path: P, chars: &CharMap<T>) -> Result<(), Error> {
+        let path = path.as_ref();
+        let bytes = match is_json(path) {
+            true => self.to_json(chars)?.into_bytes(),
+            false => self.to_bytes(chars)?,
+        };
+        std::fs::write(path, bytes).map_err(|e| Error::io(format!("Couldn't write {}", path.display())).with_source(e))
+    }
+
+    /// Load a snapshot saved by `save_snapshot`
+    pub fn load_snapshot<P: AsRef<Path>>(path: P, chars: &CharMap<T>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).map_err(|e| Error::io(format!("Couldn't read {}", path.display())).with_source(e))?;
+        match is_json(path) {
+            true => Self::from_json(&String::from_utf8_lossy(&bytes), chars),
+            false => Self::from_bytes(&bytes, chars),
+        }
+    }
+
+    fn snapshot(&self, chars: &CharMap<T>) -> Result<Snapshot, Error> {
+        let mut min = [0; N];
+        let mut max = [0; N];
+        let mut cells: BTreeMap<char, Vec<Vec<isize>>> = BTreeMap::new();
+        for (i, (coord, state)) in Storage::iter(&self.spots).enumerate() {
+            for axis in 0 .. N {
+                let v = coord.at(axis);
+                min[axis] = if i == 0 { v } else { min[axis].min(v) };
+                max[axis] = if i == 0 { v } else { max[axis].max(v) };
+            }
+            let c = chars.char_for(*state)
+                .ok_or_else(|| Error::invalid_input(format!("no character for state {:?}", state)))?;
+            cells.entry(c).or_default().push(coord.dim.to_vec());
+        }
+        cells.values_mut().for_each(|coords| coords.sort());
+
+        Ok(Snapshot{
+            dimensions: N,
+            generation: self.generation,
+            min: min.to_vec(),
+            max: max.to_vec(),
+            topology: (0 .. N).map(|axis| self.topology.axis(axis)).collect(),
+            cells,
+        })
+    }
+
+    fn restore(snapshot: Snapshot, chars: &CharMap<T>) -> Result<Self, Error> {
+        if snapshot.dimensions != N {
+            return Err(Error::invalid_input(format!("snapshot is {}d, not {}d", snapshot.dimensions, N)));
+        }
+        let min = to_coord::<N>(&snapshot.min)?;
+        let max = to_coord::<N>(&snapshot.max)?;
+        let boundaries: [Boundary; N] = snapshot.topology.try_into()
+            .map_err(|_| Error::parse("snapshot's topology doesn't match its dimensions"))?;
+        boundaries.iter().try_for_each(check_boundary)?;
+
+        box_volume(&min, &max)?;
+
+        let mut cells = Vec::new();
+        for (c, coords) in snapshot.cells.iter() {
+            let state = chars.state(*c).ok_or_else(|| Error::parse(format!("unknown char {}", c)))?;
+            for coord in coords {
+                let coord = to_coord::<N>(coord)?;
+                if (0 .. N).any(|axis| coord.at(axis) < min.at(axis) || coord.at(axis) > max.at(axis)) {
+                    return Err(Error::parse(format!("cell {:?} is outside the snapshot's bounds", coord.dim)));
+                }
+                cells.push((coord, state));
+            }
+        }
+
+        // sized by the box around the cells actually there, as dense storage would grow to
+        // that anyway while they're inserted
+        let mut space = LifeSpace::empty().with_topology(Topology::new(boundaries));
+        if let Some((lo, hi)) = cell_bounds(cells.iter().map(|(coord, _)| coord)) {
+            match box_volume(&lo, &hi) {
+                Ok(volume) if volume <= cells.len().saturating_mul(MAX_SPARSENESS) => space.spots = S::with_bounds(lo, hi),
+                _ if S::DENSE => return Err(Error::invalid_input("snapshot's cells are too spread out for dense storage")),
+                _ => (),
+            }
+        }
+        space.generation = snapshot.generation;
+        for (coord, state) in cells {
+            space.spots.insert(coord, state);
+        }
+        Ok(space)
+    }
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == "json")
+}
+
+// how many cells fit between `min` and `max`, as long as they're the right way round and
+// the count fits in a usize
+fn box_volume<const N: usize>(min: &Coord<N>, max: &Coord<N>) -> Result<usize, Error> {
+    (0 .. N).try_fold(1usize, |volume, axis| {
+        let (lo, hi) = (min.at(axis), max.at(axis));
+        if hi < lo {
+            return Err(Error::parse(format!("snapshot's bounds are the wrong way round on axis {}", axis)));
+        }
+        hi.checked_sub(lo)
+            .and_then(|d| usize::try_from(d).ok())
+            .and_then(|d| d.checked_add(1))
+            .and_then(|extent| volume.checked_mul(extent))
+            .ok_or_else(|| Error::parse("snapshot's bounds are too big"))
+    })
+}
+
+// the smallest box holding all of `coords`, if there are any
+fn cell_bounds<'a, const N: usize>(coords: impl Iterator<Item = &'a Coord<N>>) -> Option<(Coord<N>, Coord<N>)> {
+    coords.fold(None, |bounds, coord| match bounds {
+        None => Some((*coord, *coord)),
+        Some((mut lo, mut hi)) => {
+            for axis in 0 .. N {
+                lo.set(axis, lo.at(axis).min(coord.at(axis)));
+                hi.set(axis, hi.at(axis).max(coord.at(axis)));
+            }
+            Some((lo, hi))
+        },
+    })
+}
+
+// stepping works out sizes from a boundary's `min` and `max`, which have to make sense
+fn check_boundary(boundary: &Boundary) -> Result<(), Error> {
+    let size = match *boundary {
+        Boundary::Unbounded => return Ok(()),
+        Boundary::Fixed{min, max} | Boundary::Torus{min, max} | Boundary::Mirror{min, max} if max >= min =>
+            max.checked_sub(min).and_then(|d| d.checked_add(1)).and_then(|size| size.checked_mul(2)),
+        _ => None,
+    };
+    size.map(|_| ()).ok_or_else(|| Error::parse(format!("bad boundary {:?} in snapshot", boundary)))
+}
+
+fn to_coord<const N: usize>(values: &[isize]) -> Result<Coord<N>, Error> {
+    let dim: [isize; N] = values.try_into()
+        .map_err(|_| Error::parse(format!("coord {:?} doesn't have {} dimensions", values, N)))?;
+    Ok(Coord::new(dim))
+}
+
+fn encode(snapshot: &Snapshot) -> Vec<u8> {
+    let mut out = MAGIC.to_vec();
+    out.push(VERSION);
+    write_unsigned(&mut out, snapshot.dimensions as u64);
+    write_unsigned(&mut out, snapshot.generation as u64);
+    for boundary in snapshot.topology.iter() {
+        let (tag, range) = match *boundary {
+            Boundary::Unbounded => (0, None),
+            Boundary::Fixed{min, max} => (1, Some((min, max))),
+            Boundary::Torus{min, max} => (2, Some((min, max))),
+            Boundary::Mirror{min, max} => (3, Some((min, max))),
+        };
+        out.push(tag);
+        if let Some((min, max)) = range {
+            write_signed(&mut out, min);
+            write_signed(&mut out, max);
+        }
+    }
+    snapshot.min.iter().chain(snapshot.max.iter()).for_each(|v| write_signed(&mut out, *v));
+
+    write_unsigned(&mut out, snapshot.cells.len() as u64);
+    for (c, coords) in snapshot.cells.iter() {
+        write_unsigned(&mut out, *c as u64);
+        write_unsigned(&mut out, coords.len() as u64);
+        for coord in coords {
+            for (v, min) in coord.iter().zip(snapshot.min.iter()) {
+                write_unsigned(&mut out, v.wrapping_sub(*min) as usize as u64);
+            }
+        }
+    }
+    out
+}
+
+// `expected` is checked before anything else is read, so a corrupt count of dimensions
+// can't send the cells off reading empty coords forever
+fn decode(bytes: &[u8], expected: usize) -> Result<Snapshot, Error> {
+    let mut reader = Reader{bytes, at: 0};
+    if reader.take(MAGIC.len())? != MAGIC {
+        return Err(Error::parse("not a life snapshot"));
+    }
+    let version = reader.byte()?;
+    if version != VERSION {
+        return Err(Error::parse(format!("unknown snapshot version {}", version)));
+    }
+
+    let dimensions = reader.unsigned()? as usize;
+    if dimensions != expected {
+        return Err(Error::invalid_input(format!("snapshot is {}d, not {}d", dimensions, expected)));
+    }
+    let generation = reader.unsigned()? as usize;
+    let mut topology = Vec::new();
+    for _ in 0 .. dimensions {
+        let boundary = match reader.byte()? {
+            0 => Boundary::Unbounded,
+            1 => Boundary::Fixed{min: reader.signed()?, max: reader.signed()?},
+            2 => Boundary::Torus{min: reader.signed()?, max: reader.signed()?},
+            3 => Boundary::Mirror{min: reader.signed()?, max: reader.signed()?},
+            tag => return Err(Error::parse(format!("unknown boundary {}", tag))),
+        };
+        topology.push(boundary);
+    }
+    let min = (0 .. dimensions).map(|_| reader.signed()).collect::<Result<Vec<isize>, Error>>()?;
+    let max = (0 .. dimensions).map(|_| reader.signed()).collect::<Result<Vec<isize>, Error>>()?;
+
+    let mut cells = BTreeMap::new();
+    for _ in 0 .. reader.unsigned()? {
+        let c = u32::try_from(reader.unsigned()?).ok().and_then(char::from_u32)
+            .ok_or_else(|| Error::parse("bad state character in snapshot"))?;
+        let count = reader.unsigned()?;
+        let mut coords = Vec::new();
+        for _ in 0 .. count {
+            let coord = min.iter()
+                .map(|min| reader.unsigned().and_then(|v| {
+                    usize::try_from(v).ok().and_then(|v| min.checked_add_unsigned(v))
+                        .ok_or_else(|| Error::parse("coord out of range in snapshot"))
+                }))
+                .collect::<Result<Vec<isize>, Error>>()?;
+            coords.push(coord);
+        }
+        cells.insert(c, coords);
+    }
+    if reader.at != bytes.len() {
+        return Err(Error::parse("trailing bytes after snapshot"));
+    }
+
+    Ok(Snapshot{dimensions, generation, min, max, topology, cells})
+}
+
+// LEB128: 7 bits at a time, low bits first, with the top bit set on all but the last byte
+fn write_unsigned(out: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        out.push((v as u8 & 0x7f) | 0x80);
+        v >>= 7;
+    }
+    out.push(v as u8);
+}
+
+// zigzag encoded, so small negative numbers stay small: 0, -1, 1, -2 ... become 0, 1, 2, 3 ...
+fn write_signed(out: &mut Vec<u8>, v: isize) {
+    let v = v as i64;
+    write_unsigned(out, ((v << 1) ^ (v >> 63)) as u64);
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    at: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], Error> {
+        let taken = self.bytes.get(self.at .. self.at + count).ok_or_else(|| Error::parse("snapshot ends early"))?;
+        self.at += count;
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8, Error> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn unsigned(&mut self) -> Result<u64, Error> {
+        let mut result = 0u64;
+        for shift in (0 .. 64).step_by(7) {
+            let byte = self.byte()?;
+            result |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(result);
+            }
+        }
+        Err(Error::parse("number too long in snapshot"))
+    }
+
+    fn signed(&mut self) -> Result<isize, Error> {
+        let v = self.unsigned()?;
+        Ok(((v >> 1) as i64 ^ -((v & 1) as i64)) as isize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::life::{DenseGrid, LifeOption, LifeRule, Neighborhood};
+
+    fn glider_4d() -> LifeSpace<4> {
+        let input = vec!(String::from(".#."), String::from("..#"), String::from("###"));
+        LifeSpace::<4>::new(&input).unwrap()
+    }
+
+    fn cells<const N: usize, S: Storage<N>>(space: &LifeSpace<N, LifeOption, S>) -> Vec<(Coord<N>, LifeOption)> {
+        let mut cells: Vec<(Coord<N>, LifeOption)> = Storage::iter(&space.spots).map(|(coord, state)| (coord, *state)).collect();
+        cells.sort_by_key(|(coord, _)| *coord);
+        cells
+    }
+
+    #[test]
+    fn test_resume() {
+        // stepping 6 times matches stepping 3, saving, loading and stepping 3 more
+        let neighborhood = Neighborhood::moore(1);
+        let rule = LifeRule::conway();
+        let mut straight = glider_4d();
+        straight.run(&neighborhood, &rule, 6);
+
+        let mut paused = glider_4d();
+        paused.run(&neighborhood, &rule, 3);
+        let bytes = paused.to_bytes(&CharMap::seats()).unwrap();
+        let json = paused.to_json(&CharMap::seats()).unwrap();
+        for mut resumed in [
+            LifeSpace::<4>::from_bytes(&bytes, &CharMap::seats()).unwrap(),
+            LifeSpace::<4>::from_json(&json, &CharMap::seats()).unwrap(),
+        ] {
+            assert_eq!(cells(&resumed), cells(&paused));
+            resumed.run(&neighborhood, &rule, 3);
+            assert_eq!(resumed.generation, 6);
+            assert_eq!(cells(&resumed), cells(&straight));
+            assert_eq!(resumed.count(LifeOption::Occupied), 848);
+        }
+        assert!(bytes.len() < json.len() / 10);
+    }
+
+    #[test]
+    fn test_topology_and_storage() {
+        let torus = Topology::torus(Coord::new_2d(-2, -2), Coord::new_2d(2, 2))
+            .with_axis(1, Boundary::Mirror{min: -2, max: 2});
+        let input = vec!(String::from("#L."), String::from("L#."));
+        let mut space = LifeSpace::<2>::new(&input).unwrap().with_topology(torus);
+        space.generation = 1_000_000;
+        space.spots.insert(Coord::new_2d(-2, -2), LifeOption::Occupied);
+
+        let bytes = space.to_bytes(&CharMap::seats()).unwrap();
+        let dense = LifeSpace::<2, LifeOption, DenseGrid<2>>::from_bytes(&bytes, &CharMap::seats()).unwrap();
+        assert_eq!(dense.topology, torus);
+        assert_eq!(dense.generation, 1_000_000);
+        assert_eq!(dense.spots.origin(), Coord::new_2d(-2, -2));
+        assert_eq!(dense.spots.extent(), [4, 5]);
+        assert_eq!(cells(&dense), cells(&space));
+
+        let json = space.to_json(&CharMap::seats()).unwrap();
+        assert!(json.contains("\"Torus\""));
+        assert_eq!(LifeSpace::<2>::from_json(&json, &CharMap::seats()).unwrap().topology, torus);
+    }
+
+    #[test]
+    fn test_files() {
+        let dir = std::env::temp_dir().join(format!("life_snapshot_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let space = glider_4d();
+        for name in ["space.json", "space.life"] {
+            let path = dir.join(name);
+            space.save_snapshot(&path, &CharMap::seats()).unwrap();
+            let loaded = LifeSpace::<4>::load_snapshot(&path, &CharMap::seats()).unwrap();
+            assert_eq!(cells(&loaded), cells(&space));
+        }
+        assert!(std::fs::read_to_string(dir.join("space.json")).unwrap().starts_with('{'));
+        assert!(std::fs::read(dir.join("space.life")).unwrap().starts_with(MAGIC));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_empty() {
+        let space = LifeSpace::<3>::empty();
+        let resumed = LifeSpace::<3>::from_bytes(&space.to_bytes(&CharMap::seats()).unwrap(), &CharMap::seats()).unwrap();
+        assert!(resumed.spots.is_empty());
+    }
+
+    #[test]
+    fn test_errors() {
+        let space = glider_4d();
+        let bytes = space.to_bytes(&CharMap::seats()).unwrap();
+        assert_eq!(LifeSpace::<3>::from_bytes(&bytes, &CharMap::seats()).err().unwrap().to_string(), "snapshot is 4d, not 3d");
+        assert!(LifeSpace::<4>::from_bytes(&bytes[.. bytes.len() - 1], &CharMap::seats()).is_err());
+        assert!(LifeSpace::<4>::from_bytes(b"LIFX", &CharMap::seats()).is_err());
+
+        // without a character for floor, the space can't be written or read
+        let no_floor = CharMap::new(&[('#', LifeOption::Occupied), ('L', LifeOption::Unoccupied)]);
+        assert!(space.to_json(&no_floor).is_err());
+        let json = space.to_json(&CharMap::seats()).unwrap();
+        assert!(LifeSpace::<4>::from_json(&json, &no_floor).is_err());
+
+        let outside = json.replacen("\"max\": [\n    2,", "\"max\": [\n    1,", 1);
+        assert_ne!(outside, json);
+        assert!(LifeSpace::<4>::from_json(&outside, &CharMap::seats()).is_err());
+        let err = LifeSpace::<4>::from_json("{\"dimensions\": 4,\n oops", &CharMap::seats()).err().unwrap();
+        assert_eq!(err.span().map(|span| span.line), Some(2));
+    }
+
+    fn json_2d(min: &str, max: &str, topology: &str, cells: &str) -> String {
+        format!("{{\"dimensions\": 2, \"generation\": 0, \"min\": {}, \"max\": {}, \"topology\": {}, \"cells\": {{\"#\": {}}}}}",
+            min, max, topology, cells)
+    }
+
+    #[test]
+    fn test_corrupt_json_bounds() {
+        let unbounded = "[\"Unbounded\", \"Unbounded\"]";
+        let load = |json: String| LifeSpace::<2, LifeOption, DenseGrid<2>>::from_json(&json, &CharMap::seats());
+        assert!(load(json_2d("[0, 0]", "[1, 1]", unbounded, "[[1, 1]]")).is_ok());
+
+        let err = load(json_2d("[0, 5]", "[1, 1]", unbounded, "[]")).err().unwrap();
+        assert_eq!(err.to_string(), "snapshot's bounds are the wrong way round on axis 1");
+        let err = load(json_2d("[-9223372036854775808, 0]", "[9223372036854775807, 0]", unbounded, "[]")).err().unwrap();
+        assert_eq!(err.to_string(), "snapshot's bounds are too big");
+        let err = load(json_2d("[-4611686018427387904, -4611686018427387904]", "[4611686018427387904, 4611686018427387904]", unbounded, "[]")).err().unwrap();
+        assert_eq!(err.to_string(), "snapshot's bounds are too big");
+
+        // a huge box that's countable, but with one cell in it, isn't allocated up front
+        let sparse = load(json_2d("[0, 0]", "[1000000000, 1000000000]", unbounded, "[[1000000000, 1000000000]]")).unwrap();
+        assert_eq!(sparse.spots.len(), 1);
+        assert_eq!(sparse.spots.extent(), [1, 1]);
+
+        // two cells at opposite corners of it can't be held densely, but a hash map is fine
+        let corners = json_2d("[0, 0]", "[1000000000, 1000000000]", unbounded, "[[0, 0], [1000000000, 1000000000]]");
+        let err = load(corners.clone()).err().unwrap();
+        assert_eq!(err.to_string(), "snapshot's cells are too spread out for dense storage");
+        let hashed = LifeSpace::<2>::from_json(&corners, &CharMap::seats()).unwrap();
+        assert_eq!(hashed.spots.len(), 2);
+
+        let torus = "[{\"Torus\": {\"min\": 0, \"max\": -1}}, \"Unbounded\"]";
+        assert!(load(json_2d("[0, 0]", "[1, 1]", torus, "[[1, 1]]")).is_err());
+        let mirror = "[{\"Mirror\": {\"min\": -9223372036854775808, \"max\": 9223372036854775807}}, \"Unbounded\"]";
+        assert!(load(json_2d("[0, 0]", "[1, 1]", mirror, "[[1, 1]]")).is_err());
+    }
+
+    #[test]
+    fn test_corrupt_binary() {
+        // no dimensions at all, with a cell count that would never run out
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&[VERSION, 0, 0, 1, b'#', 0xff, 0xff, 0xff, 0xff, 0x0f]);
+        let err = LifeSpace::<2>::from_bytes(&bytes, &CharMap::seats()).err().unwrap();
+        assert_eq!(err.to_string(), "snapshot is 0d, not 2d");
+
+        // a cell whose offset from the bounds runs off the end of isize
+        let mut cells = BTreeMap::new();
+        cells.insert('#', vec!(vec!(isize::MIN, 0)));
+        let snapshot = Snapshot{
+            dimensions: 2,
+            generation: 0,
+            min: vec!(isize::MAX, 0),
+            max: vec!(isize::MAX, 0),
+            topology: vec!(Boundary::Unbounded; 2),
+            cells,
+        };
+        let err = LifeSpace::<2>::from_bytes(&encode(&snapshot), &CharMap::seats()).err().unwrap();
+        assert_eq!(err.to_string(), "coord out of range in snapshot");
+
+        // whatever byte is damaged, loading fails or succeeds, but doesn't panic
+        let bytes = glider_4d().with_topology(Topology::torus(Coord::new_4d(-3, -3, -3, -3), Coord::new_4d(5, 5, 5, 5)))
+            .to_bytes(&CharMap::seats()).unwrap();
+        for i in 0 .. bytes.len() {
+            for damage in [0x00, 0x01, 0x7f, 0x80, 0xff] {
+                let mut damaged = bytes.clone();
+                damaged[i] = damage;
+                let _ = LifeSpace::<4>::from_bytes(&damaged, &CharMap::seats());
+                let _ = LifeSpace::<4, LifeOption, DenseGrid<4>>::from_bytes(&damaged, &CharMap::seats());
+            }
+        }
+    }
+
+    #[test]
+    fn test_varints() {
+        for v in [0, 1, -1, 63, -64, 64, 1 << 40, -(1 << 40), isize::MAX, isize::MIN] {
+            let mut out = Vec::new();
+            write_signed(&mut out, v);
+            assert_eq!(Reader{bytes: &out, at: 0}.signed().unwrap(), v);
+        }
+        let mut out = Vec::new();
+        write_unsigned(&mut out, 300);
+        assert_eq!(out, vec!(0xac, 0x02));
+    }
+}
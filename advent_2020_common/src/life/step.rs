@@ -0,0 +1,503 @@
+use super::{Assigner, CellState, Coord, LifeOption, LifeSpace, Neighborhood, Shared, Storage};
+use crate::Error;
+use std::collections::HashSet;
+
+/// New states for cells, worked out from the last generation
+type Changes<const N: usize, T> = Vec<(Coord<N>, T)>;
+
+/// Which cells count as the neighbours of a cell when stepping a `LifeSpace`
+pub trait Neighbors<const N: usize, T = LifeOption>: Shared {
+    /// Call `visit` with each neighbour of `coord` in `space`, whether or not it's stored
+    fn visit<S: Storage<N, T>>(&self, space: &LifeSpace<N, T, S>, coord: &Coord<N>, visit: &mut dyn FnMut(Coord<N>));
+}
+
+impl<const N: usize, T> Neighbors<N, T> for Neighborhood<N> {
+    fn visit<S: Storage<N, T>>(&self, _space: &LifeSpace<N, T, S>, coord: &Coord<N>, visit: &mut dyn FnMut(Coord<N>)) {
+        for offset in self.offsets() {
+            visit(coord.offset(offset));
+        }
+    }
+}
+
+/// How many of a cell's neighbours are in each state; neighbours missing from the space aren't counted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NeighborCounts<T = LifeOption> {
+    // automata only have a handful of states, so a scan beats hashing
+    counts: Vec<(T, usize)>,
+}
+
+impl<T: CellState> NeighborCounts<T> {
+    pub fn add(&mut self, state: T) {
+        match self.counts.iter_mut().find(|(s, _)| *s == state) {
+            Some((_, count)) => *count += 1,
+            None => self.counts.push((state, 1)),
+        }
+    }
+
+    pub fn of(&self, state: T) -> usize {
+        self.counts.iter().find(|(s, _)| *s == state).map(|(_, count)| *count).unwrap_or(0)
+    }
+
+    pub fn total(&self) -> usize {
+        self.counts.iter().map(|(_, count)| count).sum()
+    }
+
+    /// Back to no neighbours, keeping the allocation for the next cell
+    pub fn clear(&mut self) {
+        self.counts.clear();
+    }
+}
+
+impl<T> Default for NeighborCounts<T> {
+    fn default() -> Self {
+        NeighborCounts{counts: Vec::new()}
+    }
+}
+
+/// How a cell changes from one generation to the next.  Given the cell's state (`None` if
+/// it's not in the space) and its neighbours' states, returns its next state, or `None`
+/// to leave it as it is.  Any `Fn` with the same signature is a rule.
+pub trait Rule<T = LifeOption>: Shared {
+    fn next(&self, current: Option<T>, neighbors: &NeighborCounts<T>) -> Option<T>;
+
+    /// Whether a cell that's not in the space can come to life; if not, stepping doesn't look at them
+    fn spawns(&self) -> bool {
+        true
+    }
+
+    /// A state that means the same as the cell not being in the space, e.g. a dead cell;
+    /// `LifeSpace::run_until_cycle` ignores these when comparing generations
+    fn background(&self) -> Option<T> {
+        None
+    }
+}
+
+impl<T, F: Fn(Option<T>, &NeighborCounts<T>) -> Option<T> + Shared> Rule<T> for F {
+    fn next(&self, current: Option<T>, neighbors: &NeighborCounts<T>) -> Option<T> {
+        self(current, neighbors)
+    }
+}
+
+/// A Life-like rule written as a rulestring, e.g. `B3/S23` for Conway's Life: a dead cell
+/// with 3 live neighbours is born, and a live cell with 2 or 3 survives.  `Occupied`
+/// cells are alive, and cells die to `Floor`; anything else counts as dead.
+/// Examples:
+/// ```
+/// use advent_2020_common::life::{LifeOption, LifeRule, NeighborCounts, Rule};
+/// let rule = LifeRule::parse("B36/S23").unwrap();
+/// assert_eq!(rule.to_string(), "B36/S23");
+///
+/// let mut counts = NeighborCounts::default();
+/// for _ in 0 .. 6 {
+///     counts.add(LifeOption::Occupied);
+/// }
+/// assert_eq!(rule.next(None, &counts), Some(LifeOption::Occupied));
+/// assert_eq!(rule.next(Some(LifeOption::Occupied), &counts), Some(LifeOption::Floor));
+/// ```
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LifeRule {
+    pub birth: Vec<usize>,
+    pub survival: Vec<usize>,
+}
+
+impl LifeRule {
+    pub fn conway() -> LifeRule {
+        LifeRule{birth: vec!(3), survival: vec!(2, 3)}
+    }
+
+    /// Parse a `B<digits>/S<digits>` rulestring; the parts may come in either order
+    pub fn parse(input: &str) -> Result<LifeRule, Error> {
+        let mut birth = None;
+        let mut survival = None;
+
+        for (i, part) in input.trim().split('/').enumerate() {
+            let mut chars = part.chars();
+            let counts = match chars.next() {
+                Some('B') | Some('b') => &mut birth,
+                Some('S') | Some('s') => &mut survival,
+                _ => return Err(Error::parse(format!("rule part {} should start with B or S", part))),
+            };
+            if counts.is_some() || i > 1 {
+                return Err(Error::parse(format!("rule {} has too many parts", input)));
+            }
+
+            let mut digits = Vec::new();
+            for c in chars {
+                let count = c.to_digit(10).ok_or_else(|| Error::parse(format!("not a neighbour count ({}) in rule {}", c, input)))?;
+                digits.push(count as usize);
+            }
+            digits.sort_unstable();
+            digits.dedup();
+            *counts = Some(digits);
+        }
+
+        match (birth, survival) {
+            (Some(birth), Some(survival)) => Ok(LifeRule{birth, survival}),
+            _ => Err(Error::parse(format!("rule {} needs both a B and an S part", input))),
+        }
+    }
+}
+
+impl std::fmt::Display for LifeRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "B")?;
+        for count in self.birth.iter() {
+            write!(f, "{}", count)?;
+        }
+        write!(f, "/S")?;
+        for count in self.survival.iter() {
+            write!(f, "{}", count)?;
+        }
+        Ok(())
+    }
+}
+
+impl Rule for LifeRule {
+    fn next(&self, current: Option<LifeOption>, neighbors: &NeighborCounts) -> Option<LifeOption> {
+        let alive = neighbors.of(LifeOption::Occupied);
+        match current {
+            Some(LifeOption::Occupied) if !self.survival.contains(&alive) => Some(LifeOption::Floor),
+            Some(LifeOption::Occupied) => None,
+            _ if self.birth.contains(&alive) => Some(LifeOption::Occupied),
+            _ => None,
+        }
+    }
+
+    // B0 would fill all of an unbounded space
+    fn spawns(&self) -> bool {
+        !self.birth.is_empty()
+    }
+
+    fn background(&self) -> Option<LifeOption> {
+        Some(LifeOption::Floor)
+    }
+}
+
+impl<const N: usize, T: CellState, S: Storage<N, T>> LifeSpace<N, T, S> {
+    /// Work out the next generation, applying `rule` to every cell in the space (and, if
+    /// the rule spawns, every missing cell next to one), then move to it.  Neighbours are
+    /// found through the space's topology.  Returns how many cells changed.
+    #[cfg(not(feature = "parallel"))]
+    pub fn step<B: Neighbors<N, T>, R: Rule<T>>(&mut self, neighbors: &B, rule: &R) -> usize {
+        self.step_serial(neighbors, rule)
+    }
+
+    /// Work out the next generation, applying `rule` to every cell in the space (and, if
+    /// the rule spawns, every missing cell next to one), then move to it.  Neighbours are
+    /// found through the space's topology.  Returns how many cells changed.  Built with
+    /// the `parallel` feature, so the work is spread across threads by `step_parallel`.
+    #[cfg(feature = "parallel")]
+    pub fn step<B: Neighbors<N, T>, R: Rule<T>>(&mut self, neighbors: &B, rule: &R) -> usize {
+        self.step_parallel(neighbors, rule)
+    }
+
+    /// `step` on the current thread, whatever features are enabled
+    pub fn step_serial<B: Neighbors<N, T>, R: Rule<T>>(&mut self, neighbors: &B, rule: &R) -> usize {
+        let mut changes = Vec::new();
+        let mut missing = HashSet::new();
+        self.next_states(Storage::iter(&self.spots).map(|(coord, value)| (coord, *value)), neighbors, rule, &mut changes, &mut missing);
+        self.next_spawned(missing.iter(), neighbors, rule, &mut changes);
+        self.apply(changes)
+    }
+
+    /// `step`, with the cells split into chunks that are worked out on rayon's thread pool.
+    /// Each cell's next state only depends on the last generation, so the result is exactly
+    /// the same as `step_serial`'s.
+    #[cfg(feature = "parallel")]
+    pub fn step_parallel<B: Neighbors<N, T>, R: Rule<T>>(&mut self, neighbors: &B, rule: &R) -> usize {
+        use rayon::prelude::*;
+
+        let cells: Vec<(Coord<N>, T)> = Storage::iter(&self.spots).map(|(coord, value)| (coord, *value)).collect();
+        let (changes, missing): (Vec<Changes<N, T>>, Vec<HashSet<Coord<N>>>) = cells.par_chunks(chunk_size(cells.len()))
+            .map(|chunk| {
+                let mut changes = Vec::new();
+                let mut missing = HashSet::new();
+                self.next_states(chunk.iter().copied(), neighbors, rule, &mut changes, &mut missing);
+                (changes, missing)
+            })
+            .unzip();
+
+        // chunks can share missing neighbours, which must only be worked out once
+        let missing: HashSet<Coord<N>> = missing.into_iter().flatten().collect();
+        let missing: Vec<Coord<N>> = missing.into_iter().collect();
+        let spawned: Vec<Changes<N, T>> = missing.par_chunks(chunk_size(missing.len()))
+            .map(|chunk| {
+                let mut changes = Vec::new();
+                self.next_spawned(chunk.iter(), neighbors, rule, &mut changes);
+                changes
+            })
+            .collect();
+
+        self.apply(changes.into_iter().chain(spawned).flatten())
+    }
+
+    // the changes `rule` makes to `cells`, and (if it spawns) the missing cells next to them
+    fn next_states<I, B, R>(&self, cells: I, neighbors: &B, rule: &R, changes: &mut Changes<N, T>, missing: &mut HashSet<Coord<N>>)
+    where I: Iterator<Item = (Coord<N>, T)>, B: Neighbors<N, T>, R: Rule<T> {
+        let mut counts = NeighborCounts::default();
+        let spawns = rule.spawns();
+
+        for (coord, value) in cells {
+            counts.clear();
+            neighbors.visit(self, &coord, &mut |c| {
+                let c = match self.topology.resolve(&c) {
+                    Some(c) => c,
+                    None => return,
+                };
+                match self.spots.get(&c) {
+                    Some(v) => counts.add(*v),
+                    None if spawns => {
+                        missing.insert(c);
+                    },
+                    None => (),
+                }
+            });
+
+            match rule.next(Some(value), &counts) {
+                Some(next) if next != value => changes.push((coord, next)),
+                _ => (),
+            }
+        }
+    }
+
+    // the changes `rule` makes to cells that aren't in the space
+    fn next_spawned<'a, I, B, R>(&self, coords: I, neighbors: &B, rule: &R, changes: &mut Changes<N, T>)
+    where I: Iterator<Item = &'a Coord<N>>, B: Neighbors<N, T>, R: Rule<T> {
+        let mut counts = NeighborCounts::default();
+
+        for coord in coords {
+            counts.clear();
+            neighbors.visit(self, coord, &mut |c| if let Some(v) = self.topology.resolve(&c).and_then(|c| self.spots.get(&c)) {
+                counts.add(*v);
+            });
+
+            if let Some(next) = rule.next(None, &counts) {
+                changes.push((*coord, next));
+            }
+        }
+    }
+
+    // move to the next generation; every coord in `changes` is different
+    fn apply<I: IntoIterator<Item = (Coord<N>, T)>>(&mut self, changes: I) -> usize {
+        let mut assigner = Assigner::new();
+        for (coord, value) in changes {
+            assigner.insert(coord, value);
+        }
+
+        let changed = assigner.spots.len();
+        assigner.commit(self);
+        self.generation += 1;
+        changed
+    }
+
+    /// Step `generations` times.  Stops working once nothing changes, since the rest are
+    /// no-ops, but the generation count still moves on by `generations`.
+    pub fn run<B: Neighbors<N, T>, R: Rule<T>>(&mut self, neighbors: &B, rule: &R, generations: usize) {
+        let target = self.generation + generations;
+        while self.generation < target {
+            if self.step(neighbors, rule) == 0 {
+                self.generation = target;
+            }
+        }
+    }
+
+    /// Step until nothing changes, returning how many generations changed something
+    pub fn run_until_stable<B: Neighbors<N, T>, R: Rule<T>>(&mut self, neighbors: &B, rule: &R) -> usize {
+        let mut generations = 0;
+        while self.step(neighbors, rule) > 0 {
+            generations += 1;
+        }
+        generations
+    }
+}
+
+/// Fewest cells worth handing to a thread of their own
+#[cfg(feature = "parallel")]
+const MIN_CHUNK: usize = 1024;
+
+#[cfg(feature = "parallel")]
+fn chunk_size(cells: usize) -> usize {
+    (cells / rayon::current_num_threads()).max(MIN_CHUNK)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::life::{CharMap, Topology};
+
+    fn blinker() -> Vec<String> {
+        vec!(
+            String::from("....."),
+            String::from("..#.."),
+            String::from("..#.."),
+            String::from("..#.."),
+            String::from("....."),
+        )
+    }
+
+    #[test]
+    fn test_parse_rule() {
+        assert_eq!(LifeRule::parse("B3/S23").unwrap(), LifeRule::conway());
+        assert_eq!(LifeRule::parse("s32/b3").unwrap(), LifeRule::conway());
+        assert_eq!(LifeRule::parse("B/S").unwrap(), LifeRule{birth: vec!(), survival: vec!()});
+        assert!(LifeRule::parse("23/3").is_err());
+        assert!(LifeRule::parse("B3").is_err());
+        assert!(LifeRule::parse("B3/S2x").is_err());
+        assert!(LifeRule::parse("B3/S23/B4").is_err());
+    }
+
+    #[test]
+    fn test_step_blinker() {
+        let mut space = LifeSpace::<2>::new(&blinker()).unwrap();
+        let neighborhood = Neighborhood::moore(1);
+
+        assert_eq!(space.step(&neighborhood, &LifeRule::conway()), 4);
+        assert_eq!(space.count(LifeOption::Occupied), 3);
+        assert_eq!(space.at_2d(2, 1), Some(&LifeOption::Occupied));
+        assert_eq!(space.at_2d(1, 2), Some(&LifeOption::Floor));
+        assert_eq!(space.generation, 1);
+
+        space.run(&neighborhood, &LifeRule::conway(), 3);
+        assert_eq!(space.generation, 4);
+        assert_eq!(space.at_2d(1, 2), Some(&LifeOption::Occupied));
+    }
+
+    #[test]
+    fn test_run_until_stable() {
+        // a block is still life, and the stray cell dies straight away
+        let input = vec!(String::from("##...#"), String::from("##...."));
+        let mut space = LifeSpace::<2>::new(&input).unwrap();
+        assert_eq!(space.run_until_stable(&Neighborhood::moore(1), &LifeRule::conway()), 1);
+        assert_eq!(space.count(LifeOption::Occupied), 4);
+        assert_eq!(space.generation, 2);
+    }
+
+    #[test]
+    fn test_step_spawns_outside() {
+        let input = vec!(String::from("###"));
+        let mut space = LifeSpace::<2>::new(&input).unwrap();
+        space.step(&Neighborhood::moore(1), &LifeRule::conway());
+        assert_eq!(space.at_2d(-1, 1), Some(&LifeOption::Occupied));
+        assert_eq!(space.at_2d(1, 1), Some(&LifeOption::Occupied));
+    }
+
+    #[test]
+    fn test_step_topology() {
+        // walled in, the blinker can't grow past the top edge
+        let input = vec!(String::from("###"));
+        let fixed = Topology::fixed(Coord::new_2d(0, 0), Coord::new_2d(1, 2));
+        let mut space = LifeSpace::<2>::new(&input).unwrap().with_topology(fixed);
+        space.step(&Neighborhood::moore(1), &LifeRule::conway());
+        assert_eq!(space.at_2d(-1, 1), None);
+        assert_eq!(space.at_2d(1, 1), Some(&LifeOption::Occupied));
+        assert_eq!(space.count(LifeOption::Occupied), 2);
+
+        // mirrored, the row above is a copy of the top row and the columns past each end copies
+        // of the end cells, so the top row is overcrowded and the whole row below it is born
+        let mirror = Topology::mirror(Coord::new_2d(0, 0), Coord::new_2d(1, 2));
+        let mut space = LifeSpace::<2>::new(&input).unwrap().with_topology(mirror);
+        space.step(&Neighborhood::moore(1), &LifeRule::conway());
+        assert_eq!(space.count(LifeOption::Occupied), 3);
+        for column in 0 .. 3 {
+            assert_eq!(space.at_2d(0, column), Some(&LifeOption::Floor));
+            assert_eq!(space.at_2d(1, column), Some(&LifeOption::Occupied));
+        }
+    }
+
+    #[test]
+    fn test_step_torus() {
+        // a glider on a 5x5 torus moves one cell diagonally every 4 generations, so is
+        // back where it started after 20
+        let glider = vec!(
+            String::from(".#..."),
+            String::from("..#.."),
+            String::from("###.."),
+            String::from("....."),
+            String::from("....."),
+        );
+        let torus = Topology::torus(Coord::new_2d(0, 0), Coord::new_2d(4, 4));
+        let mut space = LifeSpace::<2>::new(&glider).unwrap().with_topology(torus);
+        let start = space.clone();
+        space.run(&Neighborhood::moore(1), &LifeRule::conway(), 20);
+        assert_eq!(space.spots, start.spots);
+        assert_eq!(space.spots.len(), 25);
+    }
+
+    #[test]
+    fn test_step_closure() {
+        // every cell flips between occupied and unoccupied, and nothing new appears
+        let flip = |current: Option<LifeOption>, _: &NeighborCounts<LifeOption>| match current {
+            Some(LifeOption::Occupied) => Some(LifeOption::Unoccupied),
+            Some(LifeOption::Unoccupied) => Some(LifeOption::Occupied),
+            _ => None,
+        };
+        let input = vec!(String::from("#L."));
+        let mut space = LifeSpace::<2>::new(&input).unwrap();
+        assert_eq!(space.step(&Neighborhood::von_neumann(1), &flip), 2);
+        assert_eq!(space.to_string().unwrap(), "\nx = 0 L#.\n");
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    enum Wire {
+        Empty,
+        Head,
+        Tail,
+        Conductor,
+    }
+
+    fn wireworld(current: Option<Wire>, neighbors: &NeighborCounts<Wire>) -> Option<Wire> {
+        match current {
+            Some(Wire::Head) => Some(Wire::Tail),
+            Some(Wire::Tail) => Some(Wire::Conductor),
+            Some(Wire::Conductor) if (1 ..= 2).contains(&neighbors.of(Wire::Head)) => Some(Wire::Head),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_step_wireworld() {
+        let chars = CharMap::new(&[(' ', Wire::Empty), ('H', Wire::Head), ('t', Wire::Tail), ('#', Wire::Conductor)]);
+        let input = vec!(String::from(" tH### "));
+        let mut space: LifeSpace<2, Wire> = LifeSpace::parse(&input, &chars).unwrap();
+
+        space.step(&Neighborhood::moore(1), &wireworld);
+        assert_eq!(space.to_string_with(&chars).unwrap(), "\nx = 0  #tH## \n");
+        space.run(&Neighborhood::moore(1), &wireworld, 2);
+        assert_eq!(space.to_string_with(&chars).unwrap(), "\nx = 0  ###tH \n");
+        assert_eq!(space.count(Wire::Head), 1);
+        assert!(LifeSpace::<2, Wire>::parse(&[String::from("x")], &chars).is_err());
+    }
+
+    // a fixed pseudo-random soup, big enough to be split across several chunks
+    #[cfg(feature = "parallel")]
+    fn soup(size: usize) -> Vec<String> {
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        (0 .. size).map(|_| (0 .. size).map(|_| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            if seed.is_multiple_of(3) { '#' } else { '.' }
+        }).collect()).collect()
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_step_parallel() {
+        let mut serial = LifeSpace::<2>::new(&soup(80)).unwrap();
+        let mut parallel = serial.clone();
+        for _ in 0 .. 20 {
+            let changed = serial.step_serial(&Neighborhood::moore(1), &LifeRule::conway());
+            assert_eq!(parallel.step_parallel(&Neighborhood::moore(1), &LifeRule::conway()), changed);
+            assert_eq!(parallel.spots, serial.spots);
+        }
+
+        let mut serial = LifeSpace::<3>::new(&soup(24)).unwrap();
+        let mut parallel = serial.clone();
+        let rule = LifeRule::parse("B3/S23").unwrap();
+        for _ in 0 .. 4 {
+            assert_eq!(parallel.step_parallel(&Neighborhood::moore(1), &rule), serial.step_serial(&Neighborhood::moore(1), &rule));
+            assert_eq!(parallel.spots, serial.spots);
+        }
+    }
+}
@@ -0,0 +1,236 @@
+use super::{CellState, Coord, LifeOption, Shared};
+use std::collections::HashMap;
+
+/// Where a `LifeSpace` keeps its cells.  A `HashMap` (the default) only pays for
+/// cells that exist, so suits spaces that grow without limit like day 17's cubes;
+/// a `DenseGrid` suits spaces where every cell in a box exists, like day 11's seats.
+pub trait Storage<const N: usize, T = LifeOption>: Clone + Shared {
+    /// Whether the storage takes room for the whole box around its cells, however few
+    /// there are in it
+    const DENSE: bool = false;
+
+    /// Empty storage, sized up front for cells between `min` and `max` inclusive
+    fn with_bounds(min: Coord<N>, max: Coord<N>) -> Self;
+    fn get(&self, coord: &Coord<N>) -> Option<&T>;
+    fn insert(&mut self, coord: Coord<N>, value: T);
+    fn len(&self) -> usize;
+    /// Every cell in the space, in no particular order
+    fn iter(&self) -> Box<dyn Iterator<Item = (Coord<N>, &T)> + '_>;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<const N: usize, T: CellState> Storage<N, T> for HashMap<Coord<N>, T> {
+    fn with_bounds(min: Coord<N>, max: Coord<N>) -> Self {
+        HashMap::with_capacity(box_size(&min, &max))
+    }
+
+    fn get(&self, coord: &Coord<N>) -> Option<&T> {
+        HashMap::get(self, coord)
+    }
+
+    fn insert(&mut self, coord: Coord<N>, value: T) {
+        HashMap::insert(self, coord, value);
+    }
+
+    fn len(&self) -> usize {
+        HashMap::len(self)
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = (Coord<N>, &T)> + '_> {
+        Box::new(HashMap::iter(self).map(|(coord, value)| (*coord, value)))
+    }
+}
+
+fn box_size<const N: usize>(min: &Coord<N>, max: &Coord<N>) -> usize {
+    min.dim.iter().zip(max.dim.iter())
+        .map(|(lo, hi)| if hi >= lo { (hi - lo + 1) as usize } else { 0 })
+        .product()
+}
+
+/// Cells kept in one `Vec` covering a box of the space, starting at `origin`.
+/// Inserting outside the box grows it (with some slack, so a space spreading out a
+/// cell per generation doesn't reallocate every time); looking up outside it is `None`.
+/// Examples:
+/// ```
+/// use advent_2020_common::life::{Coord, DenseGrid, LifeOption, Storage};
+/// let mut grid = DenseGrid::<2>::with_bounds(Coord::new_2d(0, 0), Coord::new_2d(9, 9));
+/// grid.insert(Coord::new_2d(3, 4), LifeOption::Occupied);
+/// grid.insert(Coord::new_2d(-2, 4), LifeOption::Floor);
+/// assert_eq!(grid.get(&Coord::new_2d(3, 4)), Some(&LifeOption::Occupied));
+/// assert_eq!(grid.get(&Coord::new_2d(5, 5)), None);
+/// assert_eq!(grid.len(), 2);
+/// ```
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct DenseGrid<const N: usize, T = LifeOption> {
+    origin: [isize; N],
+    extent: [usize; N],
+    cells: Vec<Option<T>>,
+    len: usize,
+}
+
+impl<const N: usize, T: CellState> DenseGrid<N, T> {
+    /// The lowest coord the grid currently has room for
+    pub fn origin(&self) -> Coord<N> {
+        Coord::new(self.origin)
+    }
+
+    /// How many cells the grid currently has room for along each axis
+    pub fn extent(&self) -> [usize; N] {
+        self.extent
+    }
+
+    fn index(&self, coord: &Coord<N>) -> Option<usize> {
+        let mut index = 0;
+        for i in 0 .. N {
+            let offset = coord.dim[i] - self.origin[i];
+            if offset < 0 || offset as usize >= self.extent[i] {
+                return None;
+            }
+            index = index * self.extent[i] + offset as usize;
+        }
+        Some(index)
+    }
+
+    fn coord_at(&self, index: usize) -> Coord<N> {
+        let mut dim = [0; N];
+        let mut rest = index;
+        for i in (0 .. N).rev() {
+            dim[i] = self.origin[i] + (rest % self.extent[i]) as isize;
+            rest /= self.extent[i];
+        }
+        Coord::new(dim)
+    }
+
+    // reallocate so the box also covers `coord`
+    fn grow_to(&mut self, coord: &Coord<N>) {
+        // nothing to keep, so start afresh around `coord` rather than stretching from the origin
+        if self.len == 0 {
+            *self = DenseGrid::with_bounds(*coord, *coord);
+            return;
+        }
+
+        let mut min = self.origin;
+        let mut max = [0; N];
+        for i in 0 .. N {
+            max[i] = self.origin[i] + self.extent[i] as isize - 1;
+            let slack = (self.extent[i] as isize / 2).max(1);
+            if coord.dim[i] < min[i] {
+                min[i] = coord.dim[i] - slack;
+            }
+            if coord.dim[i] > max[i] {
+                max[i] = coord.dim[i] + slack;
+            }
+        }
+
+        let mut grown = DenseGrid::with_bounds(Coord::new(min), Coord::new(max));
+        for (i, cell) in self.cells.iter().enumerate() {
+            if let Some(value) = cell {
+                let index = grown.index(&self.coord_at(i)).unwrap();
+                grown.cells[index] = Some(*value);
+            }
+        }
+        grown.len = self.len;
+        *self = grown;
+    }
+}
+
+impl<const N: usize, T: CellState> Storage<N, T> for DenseGrid<N, T> {
+    const DENSE: bool = true;
+
+    fn with_bounds(min: Coord<N>, max: Coord<N>) -> Self {
+        let mut extent = [0; N];
+        for (i, e) in extent.iter_mut().enumerate() {
+            *e = if max.dim[i] >= min.dim[i] { (max.dim[i] - min.dim[i] + 1) as usize } else { 0 };
+        }
+        DenseGrid{origin: min.dim, extent, cells: vec!(None; box_size(&min, &max)), len: 0}
+    }
+
+    fn get(&self, coord: &Coord<N>) -> Option<&T> {
+        self.index(coord).and_then(|i| self.cells[i].as_ref())
+    }
+
+    fn insert(&mut self, coord: Coord<N>, value: T) {
+        let index = match self.index(&coord) {
+            Some(index) => index,
+            None => {
+                self.grow_to(&coord);
+                self.index(&coord).unwrap()
+            }
+        };
+        if self.cells[index].replace(value).is_none() {
+            self.len += 1;
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = (Coord<N>, &T)> + '_> {
+        Box::new(self.cells.iter().enumerate()
+            .filter_map(move |(i, cell)| cell.as_ref().map(|value| (self.coord_at(i), value))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dense_grid() {
+        let mut grid = DenseGrid::<3>::with_bounds(Coord::new_3d(-1, -1, 0), Coord::new_3d(1, 1, 0));
+        assert_eq!(grid.extent(), [3, 3, 1]);
+        assert!(grid.is_empty());
+
+        grid.insert(Coord::new_3d(-1, 1, 0), LifeOption::Occupied);
+        grid.insert(Coord::new_3d(-1, 1, 0), LifeOption::Unoccupied);
+        assert_eq!(grid.len(), 1);
+        assert_eq!(grid.get(&Coord::new_3d(-1, 1, 0)), Some(&LifeOption::Unoccupied));
+        assert_eq!(grid.get(&Coord::new_3d(0, 0, 0)), None);
+        assert_eq!(grid.get(&Coord::new_3d(5, 0, 0)), None);
+    }
+
+    #[test]
+    fn test_dense_grid_grows() {
+        let mut grid = DenseGrid::<2>::with_bounds(Coord::new_2d(0, 0), Coord::new_2d(1, 1));
+        grid.insert(Coord::new_2d(1, 1), LifeOption::Occupied);
+        grid.insert(Coord::new_2d(-3, 4), LifeOption::Floor);
+
+        assert!(grid.origin().dim[0] <= -3);
+        assert_eq!(grid.get(&Coord::new_2d(1, 1)), Some(&LifeOption::Occupied));
+        assert_eq!(grid.get(&Coord::new_2d(-3, 4)), Some(&LifeOption::Floor));
+
+        let mut cells: Vec<(Coord<2>, LifeOption)> = grid.iter().map(|(c, v)| (c, *v)).collect();
+        cells.sort_by_key(|(c, _)| *c);
+        assert_eq!(cells, vec!((Coord::new_2d(-3, 4), LifeOption::Floor), (Coord::new_2d(1, 1), LifeOption::Occupied)));
+    }
+
+    #[test]
+    fn test_empty_dense_grid_moves() {
+        // an empty grid has nothing to keep, so its first cell doesn't stretch it back to the origin
+        let mut grid = DenseGrid::<2>::with_bounds(Coord::new_2d(0, 0), Coord::new_2d(-1, -1));
+        grid.insert(Coord::new_2d(1_000_000_000, 1_000_000_000), LifeOption::Occupied);
+        assert_eq!(grid.origin(), Coord::new_2d(1_000_000_000, 1_000_000_000));
+        assert_eq!(grid.extent(), [1, 1]);
+    }
+
+    #[test]
+    fn test_backends_agree() {
+        let mut sparse: HashMap<Coord<2>, LifeOption> = Storage::with_bounds(Coord::new_2d(0, 0), Coord::new_2d(2, 2));
+        let mut dense = DenseGrid::<2>::with_bounds(Coord::new_2d(0, 0), Coord::new_2d(2, 2));
+        for (i, coord) in [[0, 0], [2, 1], [1, 2], [-1, 7]].iter().enumerate() {
+            let value = if i % 2 == 0 { LifeOption::Occupied } else { LifeOption::Unoccupied };
+            Storage::insert(&mut sparse, Coord::new(*coord), value);
+            dense.insert(Coord::new(*coord), value);
+        }
+
+        assert_eq!(Storage::len(&sparse), dense.len());
+        for (coord, value) in Storage::iter(&sparse) {
+            assert_eq!(dense.get(&coord), Some(value));
+        }
+    }
+}
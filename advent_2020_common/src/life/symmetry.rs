@@ -0,0 +1,158 @@
+use super::{CellState, Coord, LifeSpace, Neighbors, Storage};
+
+/// Neighbours for a space that's its own mirror image in the plane through 0 on each of
+/// some axes, as day 17's is: it starts as a single slice at z = 0 (and w = 0), so every
+/// generation after looks the same from z = 1 as from z = -1.  Only the cells with no
+/// negative coord on a folded axis need to be stored and stepped.  A neighbour on the
+/// negative side is looked up as its mirror image, so a cell on a mirror plane counts the
+/// cells next to it twice, once for each side.
+///
+/// The space has to start symmetric, holding just its non-negative half; after that,
+/// stepping keeps it so.  `count` and `unfold` give back the whole space.
+/// Examples:
+/// ```
+/// use advent_2020_common::life::{Folded, LifeOption, LifeRule, LifeSpace, Neighborhood, Z};
+/// let input = vec!(String::from(".#."), String::from("..#"), String::from("###"));
+/// let folded = Folded::new(Neighborhood::moore(1), &[Z]);
+/// let mut half = LifeSpace::<3>::new(&input).unwrap();
+/// half.run(&folded, &LifeRule::conway(), 6);
+/// assert_eq!(folded.count(&half, LifeOption::Occupied), 112);
+///
+/// let mut whole = LifeSpace::<3>::new(&input).unwrap();
+/// whole.run(&Neighborhood::moore(1), &LifeRule::conway(), 6);
+/// assert_eq!(folded.unfold(&half).to_string().unwrap(), whole.to_string().unwrap());
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct Folded<B> {
+    neighbors: B,
+    axes: Vec<usize>,
+}
+
+impl<B> Folded<B> {
+    /// `neighbors`, folded at 0 along each of `axes`
+    pub fn new(neighbors: B, axes: &[usize]) -> Self {
+        Folded{neighbors, axes: axes.to_vec()}
+    }
+
+    pub fn axes(&self) -> &[usize] {
+        &self.axes
+    }
+
+    /// Where `coord`'s state is kept: its mirror image in the stored half
+    pub fn fold<const N: usize>(&self, coord: &Coord<N>) -> Coord<N> {
+        let mut folded = *coord;
+        for axis in self.axes.iter() {
+            folded.set(*axis, coord.at(*axis).abs());
+        }
+        folded
+    }
+
+    /// How many cells of the whole space a stored cell stands for: 2 for each folded axis
+    /// it's off the mirror plane of
+    pub fn weight<const N: usize>(&self, coord: &Coord<N>) -> usize {
+        1 << self.axes.iter().filter(|axis| coord.at(**axis) != 0).count()
+    }
+
+    /// How many cells of the whole space are in `state`
+    pub fn count<const N: usize, T: CellState, S: Storage<N, T>>(&self, space: &LifeSpace<N, T, S>, state: T) -> usize {
+        Storage::iter(&space.spots)
+            .filter(|(_, value)| **value == state)
+            .map(|(coord, _)| self.weight(&coord))
+            .sum()
+    }
+
+    /// The whole space, with the stored half reflected back across each mirror plane, e.g.
+    /// to render or print it
+    pub fn unfold<const N: usize, T: CellState, S: Storage<N, T>>(&self, space: &LifeSpace<N, T, S>) -> LifeSpace<N, T, S> {
+        let mut whole = space.clone();
+        for (coord, value) in Storage::iter(&space.spots) {
+            let mut images = vec!(coord);
+            for axis in self.axes.iter().filter(|axis| coord.at(**axis) != 0) {
+                let reflected: Vec<Coord<N>> = images.iter()
+                    .map(|image| {
+                        let mut reflected = *image;
+                        reflected.set(*axis, -image.at(*axis));
+                        reflected
+                    })
+                    .collect();
+                images.extend(reflected);
+            }
+            for image in images.into_iter().skip(1) {
+                whole.spots.insert(image, *value);
+            }
+        }
+        whole
+    }
+}
+
+impl<const N: usize, T, B: Neighbors<N, T>> Neighbors<N, T> for Folded<B> {
+    fn visit<S: Storage<N, T>>(&self, space: &LifeSpace<N, T, S>, coord: &Coord<N>, visit: &mut dyn FnMut(Coord<N>)) {
+        self.neighbors.visit(space, coord, &mut |c| visit(self.fold(&c)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::life::{LifeOption, LifeRule, Neighborhood, W, Z};
+
+    fn glider() -> Vec<String> {
+        vec!(String::from(".#."), String::from("..#"), String::from("###"))
+    }
+
+    fn occupied<const N: usize>(space: &LifeSpace<N>) -> Vec<Coord<N>> {
+        let mut cells: Vec<Coord<N>> = space.spots.iter()
+            .filter(|(_, state)| **state == LifeOption::Occupied)
+            .map(|(coord, _)| *coord)
+            .collect();
+        cells.sort();
+        cells
+    }
+
+    #[test]
+    fn test_fold_and_weight() {
+        let folded = Folded::new(Neighborhood::<4>::moore(1), &[Z, W]);
+        assert_eq!(folded.fold(&Coord::new_4d(-1, -2, -3, 4)), Coord::new_4d(-1, -2, 3, 4));
+        assert_eq!(folded.weight(&Coord::new_4d(-1, -2, 0, 0)), 1);
+        assert_eq!(folded.weight(&Coord::new_4d(5, 5, 2, 0)), 2);
+        assert_eq!(folded.weight(&Coord::new_4d(5, 5, 2, 1)), 4);
+
+        // a cell on the plane sees each of the 9 cells at z = 1 twice
+        let mut seen = Vec::new();
+        let folded = Folded::new(Neighborhood::<3>::moore(1), &[Z]);
+        Neighbors::visit(&folded, &LifeSpace::<3>::empty(), &Coord::new_3d(0, 0, 0), &mut |c| seen.push(c));
+        assert_eq!(seen.len(), 26);
+        assert_eq!(seen.iter().filter(|c| c.at(Z) == 1).count(), 18);
+        assert!(seen.iter().all(|c| c.at(Z) >= 0));
+    }
+
+    #[test]
+    fn test_matches_whole_space() {
+        let rule = LifeRule::conway();
+        let folded = Folded::new(Neighborhood::moore(1), &[Z, W]);
+        let mut half = LifeSpace::<4>::new(&glider()).unwrap();
+        let mut whole = LifeSpace::<4>::new(&glider()).unwrap();
+        for _ in 0 .. 4 {
+            half.step(&folded, &rule);
+            whole.step(&Neighborhood::moore(1), &rule);
+            assert!(half.spots.keys().all(|c| c.at(Z) >= 0 && c.at(W) >= 0));
+            assert_eq!(folded.count(&half, LifeOption::Occupied), whole.count(LifeOption::Occupied));
+            assert_eq!(occupied(&folded.unfold(&half)), occupied(&whole));
+        }
+        // the mirror planes are stored once, so early on the saving is less than 4x
+        assert!(half.spots.len() * 2 < whole.spots.len());
+    }
+
+    #[test]
+    fn test_radius_2() {
+        // reflections reach past the cells next to the plane too
+        let rule = LifeRule::parse("B5/S46").unwrap();
+        let folded = Folded::new(Neighborhood::moore(2), &[Z]);
+        let mut half = LifeSpace::<3>::new(&glider()).unwrap();
+        let mut whole = LifeSpace::<3>::new(&glider()).unwrap();
+        half.run(&folded, &rule, 3);
+        whole.run(&Neighborhood::moore(2), &rule, 3);
+        assert_eq!(occupied(&folded.unfold(&half)), occupied(&whole));
+    }
+}
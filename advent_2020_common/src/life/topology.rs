@@ -0,0 +1,173 @@
+use super::Coord;
+use serde::{Deserialize, Serialize};
+
+/// What happens at the edge of a space along one axis
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum Boundary {
+    /// No edge; every coord exists
+    Unbounded,
+    /// Only coords from `min` to `max` inclusive exist; past the edge is a wall
+    Fixed{min: isize, max: isize},
+    /// Coords from `min` to `max`, with stepping off one edge coming back in at the other
+    Torus{min: isize, max: isize},
+    /// Coords from `min` to `max`, with the edge acting as a mirror: one step past `max`
+    /// is `max` again, two steps is `max - 1`, and so on
+    Mirror{min: isize, max: isize},
+}
+
+impl Boundary {
+    /// Where `v` really is along this axis, or `None` if it's past a wall.  An axis with
+    /// `max` below `min` has no coords at all, so nothing resolves on it.
+    pub fn resolve(&self, v: isize) -> Option<isize> {
+        match *self {
+            Boundary::Unbounded => Some(v),
+            Boundary::Fixed{min, max} if v < min || v > max => None,
+            Boundary::Fixed{..} => Some(v),
+            Boundary::Torus{min, max} | Boundary::Mirror{min, max} if max < min => None,
+            // in i128, so a box as wide as isize itself doesn't overflow
+            Boundary::Torus{min, max} => {
+                let size = max as i128 - min as i128 + 1;
+                Some((min as i128 + (v as i128 - min as i128).rem_euclid(size)) as isize)
+            },
+            Boundary::Mirror{min, max} => {
+                let size = max as i128 - min as i128 + 1;
+                match (v as i128 - min as i128).rem_euclid(2 * size) {
+                    m if m < size => Some((min as i128 + m) as isize),
+                    m => Some((min as i128 + 2 * size - 1 - m) as isize),
+                }
+            },
+        }
+    }
+}
+
+/// The shape of an `N` dimensional space: a `Boundary` for each axis.  Neighbour lookups
+/// go through `resolve`, so a torus's neighbours wrap and a fixed grid's edge cells have
+/// fewer neighbours.
+/// Examples:
+/// ```
+/// use advent_2020_common::life::{Boundary, Coord, Topology, Y};
+/// // day 3's slope: rows stop at the bottom, columns repeat to the right
+/// let slope = Topology::fixed(Coord::new_2d(0, 0), Coord::new_2d(10, 10))
+///     .with_axis(Y, Boundary::Torus{min: 0, max: 10});
+/// assert_eq!(slope.resolve(&Coord::new_2d(4, 12)), Some(Coord::new_2d(4, 1)));
+/// assert_eq!(slope.resolve(&Coord::new_2d(11, 0)), None);
+/// ```
+///
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Topology<const N: usize> {
+    boundaries: [Boundary; N],
+}
+
+impl<const N: usize> Topology<N> {
+    pub fn new(boundaries: [Boundary; N]) -> Self {
+        Topology{boundaries}
+    }
+
+    /// No edges anywhere, the default
+    pub fn unbounded() -> Self {
+        Topology{boundaries: [Boundary::Unbounded; N]}
+    }
+
+    /// A box from `min` to `max` inclusive, walled in on every side
+    pub fn fixed(min: Coord<N>, max: Coord<N>) -> Self {
+        Topology::from_bounds(min, max, |min, max| Boundary::Fixed{min, max})
+    }
+
+    /// A box from `min` to `max` inclusive that wraps around on every axis
+    pub fn torus(min: Coord<N>, max: Coord<N>) -> Self {
+        Topology::from_bounds(min, max, |min, max| Boundary::Torus{min, max})
+    }
+
+    /// A box from `min` to `max` inclusive, mirrored at every edge
+    pub fn mirror(min: Coord<N>, max: Coord<N>) -> Self {
+        Topology::from_bounds(min, max, |min, max| Boundary::Mirror{min, max})
+    }
+
+    fn from_bounds(min: Coord<N>, max: Coord<N>, boundary: fn(isize, isize) -> Boundary) -> Self {
+        let mut boundaries = [Boundary::Unbounded; N];
+        for (i, b) in boundaries.iter_mut().enumerate() {
+            *b = boundary(min.dim[i], max.dim[i]);
+        }
+        Topology{boundaries}
+    }
+
+    /// This topology with a different boundary along `axis`
+    pub fn with_axis(mut self, axis: usize, boundary: Boundary) -> Self {
+        self.boundaries[axis] = boundary;
+        self
+    }
+
+    pub fn axis(&self, axis: usize) -> Boundary {
+        self.boundaries[axis]
+    }
+
+    /// Where `coord` really is in the space, or `None` if it's past a wall
+    pub fn resolve(&self, coord: &Coord<N>) -> Option<Coord<N>> {
+        let mut dim = coord.dim;
+        for (d, boundary) in dim.iter_mut().zip(self.boundaries.iter()) {
+            *d = boundary.resolve(*d)?;
+        }
+        Some(Coord::new(dim))
+    }
+}
+
+impl<const N: usize> Default for Topology<N> {
+    fn default() -> Self {
+        Topology::unbounded()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_boundary() {
+        let fixed = Boundary::Fixed{min: -1, max: 2};
+        assert_eq!(fixed.resolve(-2), None);
+        assert_eq!(fixed.resolve(2), Some(2));
+        assert_eq!(fixed.resolve(3), None);
+
+        let torus = Boundary::Torus{min: -1, max: 2};
+        assert_eq!(torus.resolve(-2), Some(2));
+        assert_eq!(torus.resolve(3), Some(-1));
+        assert_eq!(torus.resolve(11), Some(-1));
+
+        let mirror = Boundary::Mirror{min: -1, max: 2};
+        let resolved: Vec<Option<isize>> = (-5 ..= 6).map(|v| mirror.resolve(v)).collect();
+        let expected = [2, 1, 0, -1, -1, 0, 1, 2, 2, 1, 0, -1];
+        assert_eq!(resolved, expected.iter().map(|v| Some(*v)).collect::<Vec<_>>());
+
+        assert_eq!(Boundary::Unbounded.resolve(-100), Some(-100));
+    }
+
+    #[test]
+    fn test_topology() {
+        let torus = Topology::torus(Coord::new_3d(0, 0, 0), Coord::new_3d(2, 2, 2))
+            .with_axis(2, Boundary::Unbounded);
+        assert_eq!(torus.resolve(&Coord::new_3d(-1, 3, -7)), Some(Coord::new_3d(2, 0, -7)));
+        assert_eq!(Topology::<3>::default().resolve(&Coord::new_3d(-1, 3, -7)), Some(Coord::new_3d(-1, 3, -7)));
+
+        let fixed = Topology::fixed(Coord::new_2d(0, 0), Coord::new_2d(2, 2));
+        assert_eq!(fixed.resolve(&Coord::new_2d(0, -1)), None);
+        assert_eq!(fixed.axis(0), Boundary::Fixed{min: 0, max: 2});
+    }
+
+    #[test]
+    fn test_empty_and_huge_axes() {
+        // max below min leaves nothing on the axis, rather than dividing by a size of 0
+        for empty in [Boundary::Fixed{min: 1, max: 0}, Boundary::Torus{min: 0, max: -1}, Boundary::Mirror{min: 5, max: -5}] {
+            assert_eq!(empty.resolve(0), None, "{:?}", empty);
+        }
+        assert_eq!(Topology::torus(Coord::new_2d(0, 0), Coord::new_2d(-1, -1)).resolve(&Coord::new_2d(0, 0)), None);
+        assert_eq!(Topology::mirror(Coord::new_2d(0, 0), Coord::new_2d(-1, -1)).resolve(&Coord::new_2d(3, 3)), None);
+
+        // and a box as wide as isize doesn't overflow working out its size
+        let torus = Boundary::Torus{min: isize::MIN, max: isize::MAX};
+        assert_eq!(torus.resolve(5), Some(5));
+        assert_eq!(torus.resolve(isize::MAX), Some(isize::MAX));
+        let mirror = Boundary::Mirror{min: isize::MIN, max: isize::MAX};
+        assert_eq!(mirror.resolve(isize::MIN), Some(isize::MIN));
+        assert_eq!(Boundary::Mirror{min: 0, max: isize::MAX}.resolve(-1), Some(0));
+    }
+}
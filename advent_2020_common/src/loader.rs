@@ -0,0 +1,122 @@
+use std::io::prelude::*;
+
+/// Path that means "read the puzzle input from stdin"
+pub const STDIN_PATH: &str = "-";
+
+const BOM: char = '\u{feff}';
+
+/// Whether the empty line after a final newline is returned as a line of its own
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TrailingLine {
+    Keep,
+    Drop,
+}
+
+/// Read raw puzzle input from a file, or from stdin if the path is '-'.
+/// A UTF-8 byte order mark is stripped and CRLF line endings are converted to LF,
+/// so solutions only ever see '\n'.
+///
+pub fn read_source(path: &str) -> Result<String, std::io::Error> {
+    if path == STDIN_PATH {
+        read_from(std::io::stdin().lock())
+    } else {
+        read_from(std::fs::File::open(path)?)
+    }
+}
+
+fn read_from<R: Read>(mut reader: R) -> Result<String, std::io::Error> {
+    let mut contents = String::new();
+    reader.read_to_string(&mut contents)?;
+    Ok(normalise(&contents))
+}
+
+/// Strip a byte order mark and convert CRLF line endings to LF
+/// Examples:
+/// ```
+/// use advent_2020_common::loader::normalise;
+/// assert_eq!(normalise("\u{feff}abc\r\ndef\n"), "abc\ndef\n");
+/// ```
+///
+pub fn normalise(contents: &str) -> String {
+    contents.strip_prefix(BOM).unwrap_or(contents).replace("\r\n", "\n")
+}
+
+/// Split puzzle input into lines, accepting LF or CRLF line endings and an optional byte order mark
+/// Examples:
+/// ```
+/// use advent_2020_common::loader::{split_lines, TrailingLine};
+/// assert_eq!(split_lines("abc\r\ndef\n", TrailingLine::Drop), vec!("abc", "def"));
+/// assert_eq!(split_lines("abc\r\ndef\n", TrailingLine::Keep), vec!("abc", "def", ""));
+/// ```
+///
+pub fn split_lines(contents: &str, trailing: TrailingLine) -> Vec<String> {
+    let contents = contents.strip_prefix(BOM).unwrap_or(contents);
+    let mut lines: Vec<String> = contents.split('\n')
+        .map(|line| String::from(line.strip_suffix('\r').unwrap_or(line)))
+        .collect();
+
+    if trailing == TrailingLine::Drop && lines.last().map(|l| l.is_empty()).unwrap_or(false) {
+        lines.pop();
+    }
+    lines
+}
+
+/// Find the input path in a day binary's arguments (not including the program name):
+/// either `--input path` / `--input=path`, or else the first argument that isn't an option.
+/// Examples:
+/// ```
+/// use advent_2020_common::loader::input_path_from_args;
+/// let args: Vec<String> = vec!("--bench", "--input", "other.txt").iter().map(|s| String::from(*s)).collect();
+/// assert_eq!(input_path_from_args(&args), Some(String::from("other.txt")));
+/// ```
+///
+pub fn input_path_from_args(args: &[String]) -> Option<String> {
+    let mut positional = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--input" {
+            return args.next().cloned();
+        } else if let Some(path) = arg.strip_prefix("--input=") {
+            return Some(String::from(path));
+        } else if positional.is_none() && (arg == STDIN_PATH || !arg.starts_with('-')) {
+            positional = Some(arg.clone());
+        }
+    }
+    positional
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_lines_line_endings() {
+        let expected = vec!("abc", "", "def");
+        assert_eq!(split_lines("abc\n\ndef", TrailingLine::Drop), expected);
+        assert_eq!(split_lines("abc\r\n\r\ndef\r\n", TrailingLine::Drop), expected);
+        assert_eq!(split_lines("\u{feff}abc\r\n\ndef\n", TrailingLine::Drop), expected);
+    }
+
+    #[test]
+    fn test_split_lines_trailing() {
+        assert_eq!(split_lines("abc\n", TrailingLine::Keep), vec!("abc", ""));
+        assert_eq!(split_lines("abc", TrailingLine::Keep), vec!("abc"));
+        assert_eq!(split_lines("abc\n\n", TrailingLine::Drop), vec!("abc", ""));
+        assert_eq!(split_lines("", TrailingLine::Drop), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_read_from() {
+        let result = read_from("\u{feff}1\r\n2\r\n".as_bytes()).unwrap();
+        assert_eq!(result, "1\n2\n");
+    }
+
+    #[test]
+    fn test_input_path_from_args() {
+        let args = |input: &[&str]| -> Vec<String> { input.iter().map(|s| String::from(*s)).collect() };
+        assert_eq!(input_path_from_args(&args(&[])), None);
+        assert_eq!(input_path_from_args(&args(&["my_input.txt"])), Some(String::from("my_input.txt")));
+        assert_eq!(input_path_from_args(&args(&["-"])), Some(String::from("-")));
+        assert_eq!(input_path_from_args(&args(&["a.txt", "--input=b.txt"])), Some(String::from("b.txt")));
+    }
+}
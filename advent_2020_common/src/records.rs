@@ -0,0 +1,86 @@
+/// Groups of lines separated by one or more blank lines, see `records`
+pub struct Records<'a, S> {
+    lines: &'a [S],
+}
+
+/// Split puzzle input lines into the groups separated by blank lines, e.g. one passport or
+/// one set of answers per group.  Runs of blank lines count as a single separator, and
+/// the last group doesn't need a blank line after it.
+/// Examples:
+/// ```
+/// let lines = advent_2020_common::to_lines("a\nb\n\nc\n\n\nd");
+/// let groups: Vec<&[String]> = advent_2020_common::records(&lines).collect();
+/// assert_eq!(groups, vec!(&lines[0 .. 2], &lines[3 .. 4], &lines[6 .. 7]));
+/// ```
+///
+pub fn records<S: AsRef<str>>(lines: &[S]) -> Records<'_, S> {
+    Records{lines}
+}
+
+fn is_blank<S: AsRef<str>>(line: &S) -> bool {
+    line.as_ref().trim().is_empty()
+}
+
+impl<'a, S: AsRef<str>> Records<'a, S> {
+    /// Each group's lines joined into one string with the given separator
+    /// Examples:
+    /// ```
+    /// let lines = vec!("ecl:gry pid:860033327", "eyr:2020", "", "hcl:#cfa07d");
+    /// let joined: Vec<String> = advent_2020_common::records(&lines).joined(" ").collect();
+    /// assert_eq!(joined, vec!("ecl:gry pid:860033327 eyr:2020", "hcl:#cfa07d"));
+    /// ```
+    ///
+    pub fn joined(self, separator: &'a str) -> impl Iterator<Item = String> + 'a {
+        self.map(move |record| {
+            record.iter()
+                .map(|line| line.as_ref())
+                .collect::<Vec<&str>>()
+                .join(separator)
+        })
+    }
+}
+
+impl<'a, S: AsRef<str>> Iterator for Records<'a, S> {
+    type Item = &'a [S];
+
+    fn next(&mut self) -> Option<&'a [S]> {
+        let start = match self.lines.iter().position(|line| !is_blank(line)) {
+            Some(start) => start,
+            None => {
+                self.lines = &[];
+                return None;
+            }
+        };
+
+        let rest = &self.lines[start ..];
+        let end = rest.iter().position(is_blank).unwrap_or(rest.len());
+        self.lines = &rest[end ..];
+        Some(&rest[.. end])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records() {
+        let lines = vec!("", "a", "b", "", "", "c", "");
+        let result: Vec<&[&str]> = records(&lines).collect();
+        assert_eq!(result, vec!(&["a", "b"][..], &["c"][..]));
+    }
+
+    #[test]
+    fn test_records_empty() {
+        let lines: Vec<String> = vec!(String::new(), String::from("  "));
+        assert_eq!(records(&lines).count(), 0);
+        assert_eq!(records::<String>(&[]).count(), 0);
+    }
+
+    #[test]
+    fn test_records_no_final_separator() {
+        let lines = vec!("a", "", "b");
+        let result: Vec<String> = records(&lines).joined("").collect();
+        assert_eq!(result, vec!("a", "b"));
+    }
+}
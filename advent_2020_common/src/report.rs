@@ -0,0 +1,177 @@
+use super::{Error, Solution};
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// The result of solving one part of one day, as printed by `--json`.
+/// Exactly one of `answer` and `error` is set.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct PartResult {
+    pub day: usize,
+    pub part: usize,
+    pub answer: Option<String>,
+    /// Name of the part's answer type, e.g. "u64"
+    pub answer_type: String,
+    /// Wall time of solving the part, not counting parsing the input
+    pub elapsed_ns: u128,
+    pub error: Option<String>,
+}
+
+impl PartResult {
+    fn new<T: std::fmt::Display>(day: usize, part: usize, result: Result<T, Error>, elapsed: Duration) -> PartResult {
+        let (answer, error) = match result {
+            Ok(answer) => (Some(answer.to_string()), None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        PartResult{day, part, answer, answer_type: short_type_name::<T>(), elapsed_ns: elapsed.as_nanos(), error}
+    }
+
+    pub fn failed(&self) -> bool {
+        self.error.is_some()
+    }
+
+    pub fn to_json(&self) -> String {
+        // only strings and numbers in here, so serializing can't fail
+        serde_json::to_string(self).unwrap()
+    }
+}
+
+impl std::fmt::Display for PartResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Day {} part {}: ", self.day, self.part)?;
+        match (&self.answer, &self.error) {
+            (_, Some(error)) => write!(f, "ERROR {}", error),
+            (Some(answer), None) => write!(f, "{} ({:?})", answer, Duration::from_nanos(self.elapsed_ns as u64)),
+            (None, None) => write!(f, "no answer"),
+        }
+    }
+}
+
+// "alloc::string::String" -> "String", keeping generic arguments intact
+fn short_type_name<T>() -> String {
+    let name = std::any::type_name::<T>();
+    let path_end = name.find('<').unwrap_or(name.len());
+    match name[.. path_end].rfind("::") {
+        Some(i) => String::from(&name[i + 2 ..]),
+        None => String::from(name),
+    }
+}
+
+/// Parse the input once, then solve each requested part (1 or 2) and time it.
+/// A parse error is reported against every requested part.
+/// Examples:
+/// ```
+/// use advent_2020_common::{report::run, Error, Solution};
+///
+/// struct Len;
+///
+/// impl Solution for Len {
+///     type Input = String;
+///     type First = usize;
+///     type Second = String;
+///
+///     fn parse(&self, input: &str) -> Result<String, Error> { Ok(String::from(input)) }
+///     fn part1(&self, input: &String) -> Result<usize, Error> { Ok(input.len()) }
+///     fn part2(&self, _input: &String) -> Result<String, Error> { Err(Error::no_solution("nothing to do")) }
+/// }
+///
+/// let results = run(1, &Len, "abc", &[1, 2]);
+/// assert_eq!(results[0].answer.as_deref(), Some("3"));
+/// assert_eq!(results[0].answer_type, "usize");
+/// assert_eq!(results[1].error.as_deref(), Some("nothing to do"));
+/// assert_eq!(results[1].answer_type, "String");
+/// ```
+///
+pub fn run<S: Solution>(day: usize, solution: &S, input: &str, parts: &[usize]) -> Vec<PartResult> {
+    let parsed = match solution.parse(input) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            let msg = e.to_string();
+            return parts.iter()
+                .map(|part| match part {
+                    1 => PartResult::new::<S::First>(day, *part, Err(Error::new(e.kind(), msg.clone())), Duration::default()),
+                    _ => PartResult::new::<S::Second>(day, *part, Err(Error::new(e.kind(), msg.clone())), Duration::default()),
+                })
+                .collect();
+        }
+    };
+
+    parts.iter()
+        .map(|part| {
+            let start = Instant::now();
+            match part {
+                1 => {
+                    let result = solution.part1(&parsed);
+                    PartResult::new(day, *part, result, start.elapsed())
+                },
+                2 => {
+                    let result = solution.part2(&parsed);
+                    PartResult::new(day, *part, result, start.elapsed())
+                },
+                x => PartResult::new::<String>(day, *x, Err(Error::invalid_input(format!("no part {}", x))), Duration::default()),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::to_lines;
+
+    struct Lines;
+
+    impl Solution for Lines {
+        type Input = Vec<String>;
+        type First = usize;
+        type Second = u64;
+
+        fn parse(&self, input: &str) -> Result<Vec<String>, Error> {
+            if input.is_empty() {
+                return Err(Error::parse("empty input"));
+            }
+            Ok(to_lines(input))
+        }
+
+        fn part1(&self, input: &Vec<String>) -> Result<usize, Error> {
+            Ok(input.len())
+        }
+
+        fn part2(&self, _input: &Vec<String>) -> Result<u64, Error> {
+            Err(Error::no_solution("no second part"))
+        }
+    }
+
+    #[test]
+    fn test_short_type_name() {
+        assert_eq!(short_type_name::<u32>(), "u32");
+        assert_eq!(short_type_name::<String>(), "String");
+        assert_eq!(short_type_name::<Vec<String>>(), "Vec<alloc::string::String>");
+    }
+
+    #[test]
+    fn test_run() {
+        let results = run(4, &Lines, "a\nb", &[1, 2]);
+        assert_eq!(results[0].answer.as_deref(), Some("2"));
+        assert_eq!(results[0].answer_type, "usize");
+        assert!(!results[0].failed());
+        assert_eq!(results[1].answer, None);
+        assert_eq!(results[1].answer_type, "u64");
+        assert_eq!(results[1].error.as_deref(), Some("no second part"));
+        assert!(results[1].failed());
+    }
+
+    #[test]
+    fn test_run_parse_error() {
+        let results = run(4, &Lines, "", &[2]);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].part, 2);
+        assert_eq!(results[0].error.as_deref(), Some("empty input"));
+    }
+
+    #[test]
+    fn test_to_json() {
+        let result = PartResult{day: 1, part: 2, answer: Some(String::from("42")), answer_type: String::from("u32"), elapsed_ns: 1500, error: None};
+        assert_eq!(result.to_json(), r#"{"day":1,"part":2,"answer":"42","answer_type":"u32","elapsed_ns":1500,"error":null}"#);
+        assert_eq!(result.to_string(), "Day 1 part 2: 42 (1.5µs)");
+    }
+}
@@ -0,0 +1,47 @@
+use super::Error;
+
+/// A day's puzzle: how to turn the raw input text into something the parts can work on,
+/// and how to solve each part from that.
+///
+/// Anything a day needs beyond its input (e.g. day 7's target bag color) lives on the 
+/// implementing type, so callers can run any day the same way:
+/// ```
+/// use advent_2020_common::{Error, Solution};
+///
+/// struct Sum;
+///
+/// impl Solution for Sum {
+///     type Input = Vec<u32>;
+///     type First = u32;
+///     type Second = usize;
+///
+///     fn parse(&self, input: &str) -> Result<Vec<u32>, Error> {
+///         input.lines().map(|s| Ok(s.parse()?)).collect()
+///     }
+///
+///     fn part1(&self, input: &Vec<u32>) -> Result<u32, Error> {
+///         Ok(input.iter().sum())
+///     }
+///
+///     fn part2(&self, input: &Vec<u32>) -> Result<usize, Error> {
+///         Ok(input.len())
+///     }
+/// }
+///
+/// let parsed = Sum.parse("1\n2\n3").unwrap();
+/// assert_eq!(Sum.part1(&parsed).unwrap(), 6);
+/// assert_eq!(Sum.part2(&parsed).unwrap(), 3);
+/// ```
+///
+pub trait Solution {
+    /// The parsed form of the puzzle input, shared by both parts
+    type Input;
+    /// Answer type of part 1
+    type First: std::fmt::Display;
+    /// Answer type of part 2
+    type Second: std::fmt::Display;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error>;
+    fn part1(&self, input: &Self::Input) -> Result<Self::First, Error>;
+    fn part2(&self, input: &Self::Input) -> Result<Self::Second, Error>;
+}
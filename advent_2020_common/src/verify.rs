@@ -0,0 +1,172 @@
+use super::{Context, Error, Solution};
+
+/// The known answers for a day's input, as checked in to its answers.txt:
+/// ```text
+/// # comments and blank lines are ignored
+/// part1: 858496
+/// part2: 263819430
+/// ```
+#[derive(Debug, PartialEq, Default)]
+pub struct Answers {
+    pub part1: Option<String>,
+    pub part2: Option<String>,
+}
+
+impl Answers {
+    /// Examples:
+    /// ```
+    /// use advent_2020_common::verify::Answers;
+    /// let answers = Answers::parse("part1: 12\n").unwrap();
+    /// assert_eq!(answers, Answers{part1: Some(String::from("12")), part2: None});
+    /// ```
+    ///
+    pub fn parse(input: &str) -> Result<Answers, Error> {
+        let mut answers = Answers::default();
+
+        for (i, line) in input.lines().map(|l| l.trim()).enumerate() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut split = line.splitn(2, ':');
+            let key = split.next().unwrap_or("").trim();
+            let value = match split.next() {
+                Some(v) => String::from(v.trim()),
+                None => return Err(Error::parse("answer line without ':'").at_line(i + 1)),
+            };
+
+            match key {
+                "part1" => answers.part1 = Some(value),
+                "part2" => answers.part2 = Some(value),
+                x => return Err(Error::parse(format!("unknown answer key {}", x)).at_line(i + 1)),
+            }
+        }
+
+        Ok(answers)
+    }
+
+    pub fn from_file(filename: &str) -> Result<Answers, Error> {
+        let contents = std::fs::read_to_string(filename).with_context(|| format!("Couldn't read answers file {}", filename))?;
+        Answers::parse(&contents).with_context(|| format!("Invalid answers file {}", filename))
+    }
+
+    fn for_part(&self, part: usize) -> Option<&String> {
+        match part {
+            1 => self.part1.as_ref(),
+            _ => self.part2.as_ref(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    Pass,
+    Fail{expected: String, actual: String},
+    /// Solved, but there's no known answer to compare against
+    Unknown{actual: String},
+    Error(String),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct PartVerification {
+    pub day: usize,
+    pub part: usize,
+    pub outcome: Outcome,
+}
+
+impl PartVerification {
+    /// A wrong answer or an error; parts without a known answer don't count as failures
+    pub fn failed(&self) -> bool {
+        matches!(self.outcome, Outcome::Fail{..} | Outcome::Error(_))
+    }
+}
+
+impl std::fmt::Display for PartVerification {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Day {} part {}: ", self.day, self.part)?;
+        match &self.outcome {
+            Outcome::Pass => write!(f, "pass"),
+            Outcome::Fail{expected, actual} => write!(f, "FAIL\n  expected: {}\n  actual:   {}", expected, actual),
+            Outcome::Unknown{actual} => write!(f, "no known answer (got {})", actual),
+            Outcome::Error(msg) => write!(f, "ERROR {}", msg),
+        }
+    }
+}
+
+/// Solve both parts of a day and compare them against its known answers
+/// Examples:
+/// ```
+/// use advent_2020_common::{verify::{verify, Answers, Outcome}, Error, Solution};
+///
+/// struct Len;
+///
+/// impl Solution for Len {
+///     type Input = String;
+///     type First = usize;
+///     type Second = usize;
+///
+///     fn parse(&self, input: &str) -> Result<String, Error> { Ok(String::from(input)) }
+///     fn part1(&self, input: &String) -> Result<usize, Error> { Ok(input.len()) }
+///     fn part2(&self, input: &String) -> Result<usize, Error> { Ok(input.len() + 1) }
+/// }
+///
+/// let answers = Answers::parse("part1: 3\npart2: 3").unwrap();
+/// let result = verify(1, &Len, "abc", &answers);
+/// assert_eq!(result[0].outcome, Outcome::Pass);
+/// assert_eq!(result[1].outcome, Outcome::Fail{expected: String::from("3"), actual: String::from("4")});
+/// ```
+///
+pub fn verify<S: Solution>(day: usize, solution: &S, input: &str, answers: &Answers) -> Vec<PartVerification> {
+    let parsed = match solution.parse(input) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            return (1 ..= 2)
+                .map(|part| PartVerification{day, part, outcome: Outcome::Error(e.to_string())})
+                .collect();
+        }
+    };
+
+    (1 ..= 2)
+        .map(|part| {
+            let actual = match part {
+                1 => solution.part1(&parsed).map(|a| a.to_string()),
+                _ => solution.part2(&parsed).map(|a| a.to_string()),
+            };
+            let outcome = match (actual, answers.for_part(part)) {
+                (Err(e), _) => Outcome::Error(e.to_string()),
+                (Ok(actual), None) => Outcome::Unknown{actual},
+                (Ok(actual), Some(expected)) if actual == *expected => Outcome::Pass,
+                (Ok(actual), Some(expected)) => Outcome::Fail{expected: expected.clone(), actual},
+            };
+            PartVerification{day, part, outcome}
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_answers() {
+        let result = Answers::parse("# day 7\n\npart1: 226\r\npart2:9569\n").unwrap();
+        assert_eq!(result, Answers{part1: Some(String::from("226")), part2: Some(String::from("9569"))});
+    }
+
+    #[test]
+    fn test_parse_answers_invalid() {
+        assert!(Answers::parse("part3: 1").is_err());
+        assert!(Answers::parse("226").is_err());
+    }
+
+    #[test]
+    fn test_display() {
+        let pass = PartVerification{day: 3, part: 1, outcome: Outcome::Pass};
+        assert_eq!(pass.to_string(), "Day 3 part 1: pass");
+
+        let fail = PartVerification{day: 3, part: 2, outcome: Outcome::Fail{expected: String::from("10"), actual: String::from("11")}};
+        assert_eq!(fail.to_string(), "Day 3 part 2: FAIL\n  expected: 10\n  actual:   11");
+        assert!(fail.failed());
+        assert!(!pass.failed());
+    }
+}
@@ -1,26 +1,47 @@
 const SUM_LOOKED_FOR: u32 = 2020;
-use advent_2020_common::{find_complements, input_to_nums};
+use advent_2020_common::{find_complements, input_to_nums, to_lines, Error, Solution};
 
-pub fn first(input: &[String]) -> u32 {
-    let input = input_to_nums(input);
-    find_complements(input.as_slice(), SUM_LOOKED_FOR, true).unwrap()
+pub fn first(input: &[String]) -> Result<u32, Error> {
+    let input: Vec<u32> = input_to_nums(input)?;
+    find_complements(input.as_slice(), SUM_LOOKED_FOR, true)
+        .ok_or_else(|| Error::no_solution(format!("no 2 entries sum to {}", SUM_LOOKED_FOR)))
 }
 
 
 
-pub fn second(input: &[String]) -> u32 {
-    let input = input_to_nums(input);
+pub fn second(input: &[String]) -> Result<u32, Error> {
+    let input: Vec<u32> = input_to_nums(input)?;
     let input_len = input.len();
 
     for i in 0 .. input_len {
         let complement = SUM_LOOKED_FOR - input[i];
         let rest: &[u32] = &[&input[..i], &input[(i+1)..]].concat();
         if let Some(partial_product) = find_complements(rest, complement, true) {
-            return partial_product * input[i];
+            return Ok(partial_product * input[i]);
         }
     }
     
-    panic!("Solution not found!")
+    Err(Error::no_solution(format!("no 3 entries sum to {}", SUM_LOOKED_FOR)))
+}
+
+pub struct Day1;
+
+impl Solution for Day1 {
+    type Input = Vec<String>;
+    type First = u32;
+    type Second = u32;
+
+    fn parse(&self, input: &str) -> Result<Vec<String>, Error> {
+        Ok(to_lines(input))
+    }
+
+    fn part1(&self, input: &Vec<String>) -> Result<u32, Error> {
+        first(input)
+    }
+
+    fn part2(&self, input: &Vec<String>) -> Result<u32, Error> {
+        second(input)
+    }
 }
 
 #[cfg(test)]
@@ -36,14 +57,21 @@ mod tests {
     #[test]
     fn first_test() {
         let input = example();
-        let result = first(&input);
+        let result = first(&input).unwrap();
         assert_eq!(result, 514579);
     }
 
     #[test]
     fn second_test() { 
         let input = example();
-        let result = second(&input);
+        let result = second(&input).unwrap();
         assert_eq!(result, 241861950);
     }
+
+    #[test]
+    fn test_solution() {
+        let input = Day1.parse(&example().join("\n")).unwrap();
+        assert_eq!(Day1.part1(&input).unwrap(), 514579);
+        assert_eq!(Day1.part2(&input).unwrap(), 241861950);
+    }
 }
\ No newline at end of file
@@ -1,4 +1,4 @@
-use advent_2020_common::Error;
+use advent_2020_common::{input_to_nums, to_lines, Error, Solution};
 use std::collections::HashMap;
 // assume every adapter has a different joltage
 
@@ -65,6 +65,26 @@ fn paths_to_end(i: usize, input: &[u64], cache: &mut HashMap<usize, u64>) -> u64
     result
 }
 
+pub struct Day10;
+
+impl Solution for Day10 {
+    type Input = Vec<u32>;
+    type First = u32;
+    type Second = u64;
+
+    fn parse(&self, input: &str) -> Result<Vec<u32>, Error> {
+        input_to_nums(&to_lines(input))
+    }
+
+    fn part1(&self, input: &Vec<u32>) -> Result<u32, Error> {
+        first(&mut input.clone())
+    }
+
+    fn part2(&self, input: &Vec<u32>) -> Result<u64, Error> {
+        second(&mut input.clone())
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -149,4 +169,12 @@ mod tests {
         let result = second(&mut example3()).unwrap();
         assert_eq!(result, 4);
     }
+
+    #[test]
+    fn test_solution() {
+        let input: Vec<String> = example2().iter().map(|n| n.to_string()).collect();
+        let input = Day10.parse(&input.join("\n")).unwrap();
+        assert_eq!(Day10.part1(&input).unwrap(), 220);
+        assert_eq!(Day10.part2(&input).unwrap(), 19208);
+    }
 }
\ No newline at end of file
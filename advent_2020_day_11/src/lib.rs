@@ -1,66 +1,70 @@
-use advent_2020_common::Error;
+use advent_2020_common::{to_lines, Error, Solution};
 use advent_2020_common::life::*;
 
-pub fn first(input: &mut LifeSpace<TwoD>) -> Result<usize, Error> {   
-    loop {
-        let mut assigner = Assigner::new();
-
-        for (coord, value) in input.spots.iter() {
-            let occupied_count = input.neighbors(coord.at(TwoD::X)?, coord.at(TwoD::Y)?)
-                .filter(|v| *v == LifeOption::Occupied)
-                .count();
-            match value {
-                LifeOption::Floor => continue,
-                LifeOption::Occupied if occupied_count >= 4 => {
-                    assigner.assign(coord.at(TwoD::X)?, coord.at(TwoD::Y)?, LifeOption::Unoccupied)
-                },
-                LifeOption::Unoccupied if occupied_count == 0 => {
-                    assigner.assign(coord.at(TwoD::X)?, coord.at(TwoD::Y)?, LifeOption::Occupied)
-                }
-                _ => ()
-            };
-        }
-        
-        if assigner.empty() {
-            break;
+/// Every seat position exists from the start, so a dense grid beats a hash map
+pub type SeatLayout = LifeSpace<2, LifeOption, DenseGrid<2>>;
+
+/// Empty seats with no occupied neighbours fill up, and occupied seats with at least
+/// `tolerance` occupied neighbours empty; floor never changes
+struct SeatRule {
+    tolerance: usize,
+}
+
+impl Rule for SeatRule {
+    fn next(&self, current: Option<LifeOption>, neighbors: &NeighborCounts) -> Option<LifeOption> {
+        let occupied_count = neighbors.of(LifeOption::Occupied);
+        match current {
+            Some(LifeOption::Occupied) if occupied_count >= self.tolerance => Some(LifeOption::Unoccupied),
+            Some(LifeOption::Unoccupied) if occupied_count == 0 => Some(LifeOption::Occupied),
+            _ => None,
         }
+    }
 
-        assigner.commit(input);
+    // the seat layout never grows
+    fn spawns(&self) -> bool {
+        false
     }
+}
+
+/// Give up on seats settling after this many rounds
+const MAX_ROUNDS: usize = 1000;
 
-    // count the occupied spots
-    Ok(input.spots.iter().filter(|(_, s)| **s == LifeOption::Occupied).count())
+/// Run rounds until nobody moves, and count the occupied seats
+fn settle<B: Neighbors<2>>(input: &mut SeatLayout, neighbors: &B, rule: &SeatRule) -> Result<usize, Error> {
+    match input.run_until_cycle(neighbors, rule, MAX_ROUNDS) {
+        Evolution::FixedPoint{..} => Ok(input.count(LifeOption::Occupied)),
+        evolution => Err(Error::no_solution(format!("seats never settle: {}", evolution))),
+    }
 }
 
-pub fn second(input: &mut LifeSpace<TwoD>) -> Result<usize, Error> {
-    loop {
-        let mut assigner = Assigner::new();
-
-        for (coord, value) in input.spots.iter() {
-            let occupied_count = input.neighbors_skip_floor(coord.at(TwoD::X)?, coord.at(TwoD::Y)?)
-                .filter(|v| *v == LifeOption::Occupied)
-                .count();
-            match value {
-                LifeOption::Floor => continue,
-                LifeOption::Occupied if occupied_count >= 5 => {
-                    assigner.assign(coord.at(TwoD::X)?, coord.at(TwoD::Y)?, LifeOption::Unoccupied)
-                },
-                LifeOption::Unoccupied if occupied_count == 0 => {
-                    assigner.assign(coord.at(TwoD::X)?, coord.at(TwoD::Y)?, LifeOption::Occupied)
-                }
-                _ => ()
-            };
-        }
-        
-        if assigner.empty() {
-            break;
-        }
+pub fn first(input: &mut SeatLayout) -> Result<usize, Error> {
+    settle(input, &Neighborhood::moore(1), &SeatRule{tolerance: 4})
+}
+
+pub fn second(input: &mut SeatLayout) -> Result<usize, Error> {
+    // floor stays floor, so what each seat can see never changes
+    let sight = LineOfSight::new(&[LifeOption::Floor]).precompute(input);
+    settle(input, &sight, &SeatRule{tolerance: 5})
+}
 
-        assigner.commit(input);
+pub struct Day11;
+
+impl Solution for Day11 {
+    type Input = SeatLayout;
+    type First = usize;
+    type Second = usize;
+
+    fn parse(&self, input: &str) -> Result<SeatLayout, Error> {
+        SeatLayout::new(&to_lines(input))
+    }
+
+    fn part1(&self, input: &SeatLayout) -> Result<usize, Error> {
+        first(&mut input.clone())
     }
 
-    // count the occupied spots
-    Ok(input.spots.iter().filter(|(_, s)| **s == LifeOption::Occupied).count())
+    fn part2(&self, input: &SeatLayout) -> Result<usize, Error> {
+        second(&mut input.clone())
+    }
 }
 
 #[cfg(test)]
@@ -84,15 +88,30 @@ mod tests {
 
     #[test]
     fn test_first() {
-        let mut input = LifeSpace::<TwoD>::new(&example(), 2).unwrap();
+        let mut input = SeatLayout::new(&example()).unwrap();
         let result = first(&mut input).unwrap();
         assert_eq!(result, 37);
     }
 
     #[test]
     fn test_second() {
-        let mut input = LifeSpace::<TwoD>::new(&example(), 2).unwrap();
+        let mut input = SeatLayout::new(&example()).unwrap();
         let result = second(&mut input).unwrap();
         assert_eq!(result, 26);
     }
-}
+
+    #[test]
+    fn test_never_settles() {
+        // two seats that both fill up, then both empty again, forever
+        let mut input = SeatLayout::new(&[String::from("LL")]).unwrap();
+        let result = settle(&mut input, &Neighborhood::moore(1), &SeatRule{tolerance: 1});
+        assert_eq!(result.unwrap_err().to_string(), "seats never settle: period 2 oscillation from generation 0");
+    }
+
+    #[test]
+    fn test_solution() {
+        let input = Day11.parse(&example().join("\n")).unwrap();
+        assert_eq!(Day11.part1(&input).unwrap(), 37);
+        assert_eq!(Day11.part2(&input).unwrap(), 26);
+    }
+}
\ No newline at end of file
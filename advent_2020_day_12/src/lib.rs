@@ -1,4 +1,4 @@
-use advent_2020_common::Error;
+use advent_2020_common::{to_lines, Context, Error, Solution};
 use regex::Regex;
 use std::convert::TryFrom;
 
@@ -19,23 +19,24 @@ impl TryFrom<u32> for Directions {
             x if x == Directions::South as u32 => Ok(Directions::South),
             x if x == Directions::West as u32 => Ok(Directions::West),
             x if x == Directions::North as u32 => Ok(Directions::North),
-            x => Error::from_string(format!("convert invalid u32 to Directions {}", x)),
+            x => Err(Error::invalid_input(format!("convert invalid u32 to Directions {}", x))),
         }
     }
 }
 
 pub fn first(input: &[String]) -> Result<u32, Error> {
-    let instruction_regex = Regex::new("^(N|S|E|W|L|R|F)(\\d+)$").or(Error::new("Couldn't compile regex!"))?;
+    let instruction_regex = Regex::new("^(N|S|E|W|L|R|F)(\\d+)$")
+        .map_err(|e| Error::invalid_input("Couldn't compile regex!").with_source(e))?;
     let mut direction_mods: Vec<u32> = vec!(0, 0, 0, 0);
     let mut current_forward = Directions::East;
 
-    for line in input {
+    for (i, line) in input.iter().enumerate() {
         let caps = match instruction_regex.captures(line) {
             Some(captures) => captures,
-            _ => return Error::from_string(format!("invalid line {}", line)),
+            _ => return Err(Error::parse(format!("invalid line {}", line)).at_line(i + 1)),
         };
 
-        let amount: u32 = caps[2].parse().or(Error::new("line missing number"))?;
+        let amount: u32 = caps[2].parse().at_line(i + 1)?;
         let direction = match &caps[1] {
             "N" => Directions::North,
             "S" => Directions::South,
@@ -49,14 +50,14 @@ pub fn first(input: &[String]) -> Result<u32, Error> {
                 } else {
                     new_dir_i32
                 };
-                current_forward = Directions::try_from(new_dir_i32 as u32)?;
+                current_forward = Directions::try_from(new_dir_i32 as u32).at_line(i + 1)?;
                 continue
             },
             "R" => {
-                current_forward = Directions::try_from(((current_forward as u32) + (amount / 90)) % 4)?;
+                current_forward = Directions::try_from(((current_forward as u32) + (amount / 90)) % 4).at_line(i + 1)?;
                 continue
             },
-            x => return Error::from_string(format!("invalid instruction! {}", x)),
+            x => return Err(Error::parse(format!("invalid instruction! {}", x)).at(i + 1, 1)),
         };
 
         direction_mods[direction as usize] += amount;
@@ -73,13 +74,13 @@ fn rotate_point(x: i32, y: i32, rotation: u32 /*1, 2, 3 = 90, 180, 270*/) -> Res
         0 => 1,
         1 => 0,
         2 => -1,
-        _ => return Error::new("invalid rotation - should be 1-3 = 90-270 *")
+        _ => return Err(Error::invalid_input("invalid rotation - should be 1-3 = 90-270 *"))
     };
     let cosine: i32 = match 2 - (rotation - 1) {
         0 => 0,
         1 => -1,
         2 => 0,
-        _ => return Error::new("invalid rotation - should be 1-3 = 90-270 *")
+        _ => return Err(Error::invalid_input("invalid rotation - should be 1-3 = 90-270 *"))
     };
 
     Ok((
@@ -89,17 +90,18 @@ fn rotate_point(x: i32, y: i32, rotation: u32 /*1, 2, 3 = 90, 180, 270*/) -> Res
 }
 
 pub fn second(input: &[String]) -> Result<u32, Error> {
-    let instruction_regex = Regex::new("^(N|S|E|W|L|R|F)(\\d+)$").or(Error::new("Couldn't compile regex!"))?;
+    let instruction_regex = Regex::new("^(N|S|E|W|L|R|F)(\\d+)$")
+        .map_err(|e| Error::invalid_input("Couldn't compile regex!").with_source(e))?;
     let mut direction_mods: Vec<i32> = vec!(10, 1); // waypoint starts 10 east 1 north
     let (mut current_x, mut current_y) = (0, 0);
 
-    for line in input {
+    for (i, line) in input.iter().enumerate() {
         let caps = match instruction_regex.captures(line) {
             Some(captures) => captures,
-            _ => return Error::from_string(format!("invalid line {}", line)),
+            _ => return Err(Error::parse(format!("invalid line {}", line)).at_line(i + 1)),
         };
 
-        let amount: i32 = caps[2].parse().or(Error::new("line missing number"))?;
+        let amount: i32 = caps[2].parse().at_line(i + 1)?;
         match &caps[1] {
             "N" => direction_mods[Directions::South as usize] += amount,
             "S" => direction_mods[Directions::South as usize] -= amount,
@@ -111,23 +113,43 @@ pub fn second(input: &[String]) -> Result<u32, Error> {
             },
             "L" => {
                 let degree_90_turns = 3 - ((amount / 90) - 1) as u32;
-                let (new_x, new_y) = rotate_point(direction_mods[0], direction_mods[1], degree_90_turns)?;
+                let (new_x, new_y) = rotate_point(direction_mods[0], direction_mods[1], degree_90_turns).at_line(i + 1)?;
                 direction_mods[Directions::East as usize] = new_x;
                 direction_mods[Directions::South as usize] = new_y;
             },
             "R" => {
                 let degree_90_turns = (amount / 90) as u32;
-                let (new_x, new_y) = rotate_point(direction_mods[0], direction_mods[1], degree_90_turns)?;
+                let (new_x, new_y) = rotate_point(direction_mods[0], direction_mods[1], degree_90_turns).at_line(i + 1)?;
                 direction_mods[Directions::East as usize] = new_x;
                 direction_mods[Directions::South as usize] = new_y;
             },
-            x => return Error::from_string(format!("invalid instruction! {}", x)),
+            x => return Err(Error::parse(format!("invalid instruction! {}", x)).at(i + 1, 1)),
         };
     }
     
     Ok(current_y.abs() as u32 + current_x.abs() as u32)
 }
 
+pub struct Day12;
+
+impl Solution for Day12 {
+    type Input = Vec<String>;
+    type First = u32;
+    type Second = u32;
+
+    fn parse(&self, input: &str) -> Result<Vec<String>, Error> {
+        Ok(to_lines(input))
+    }
+
+    fn part1(&self, input: &Vec<String>) -> Result<u32, Error> {
+        first(input)
+    }
+
+    fn part2(&self, input: &Vec<String>) -> Result<u32, Error> {
+        second(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -160,4 +182,11 @@ mod tests {
         assert_eq!(east, 4);
         assert_eq!(north, -10);
     }
+
+    #[test]
+    fn test_solution() {
+        let input = Day12.parse(&example().join("\n")).unwrap();
+        assert_eq!(Day12.part1(&input).unwrap(), 25);
+        assert_eq!(Day12.part2(&input).unwrap(), 286);
+    }
 }
\ No newline at end of file
@@ -1,4 +1,4 @@
-use advent_2020_common::Error;
+use advent_2020_common::{to_lines, Context, Error, Solution};
 
 #[derive(Debug, PartialEq)]
 pub struct BusProbInput {
@@ -7,16 +7,22 @@ pub struct BusProbInput {
 }
 
 pub fn parse(input: &[String]) -> Result<BusProbInput, Error> {
-    let timestamp: u32 = input[0].parse().or(Error::new("Invalid timestamp"))?;
+    if input.len() < 2 {
+        return Err(Error::parse("expected a timestamp line and a bus id line"));
+    }
+
+    let timestamp: u32 = input[0].parse().at_line(1).context("Invalid timestamp")?;
     let mut bus_ids: Vec<u32> = Vec::new();
+    let mut column = 1;
 
     for bus_id_entry in input[1].split(',') {
         let num: Result<u32, _> = bus_id_entry.parse();
         match num {
             Ok(num) => bus_ids.push(num),
             _ if bus_id_entry == "x" => bus_ids.push(1), // 0 indicates ignored slot in schedule
-            _ => return Error::new("invalid bus id entry"),
+            _ => return Err(Error::parse(format!("invalid bus id entry {}", bus_id_entry)).at(2, column)),
         }
+        column += bus_id_entry.len() + 1;
     }
 
     Ok(BusProbInput{earliest_departure_timestamp: timestamp, in_service_bus_ids: bus_ids})
@@ -36,7 +42,7 @@ pub fn first(input: &BusProbInput) -> Result<u32, Error> {
         }
     }
 
-    let (found_mod, found_id) = max_mod.unwrap();
+    let (found_mod, found_id) = max_mod.ok_or_else(|| Error::no_solution("no buses in service"))?;
     Ok((found_id - found_mod) * found_id)
 }
 
@@ -104,6 +110,26 @@ pub fn second(input: &BusProbInput) -> Result<u64, Error> {
     Ok(result)
 }
 
+pub struct Day13;
+
+impl Solution for Day13 {
+    type Input = BusProbInput;
+    type First = u32;
+    type Second = u64;
+
+    fn parse(&self, input: &str) -> Result<BusProbInput, Error> {
+        parse(&to_lines(input))
+    }
+
+    fn part1(&self, input: &BusProbInput) -> Result<u32, Error> {
+        first(input)
+    }
+
+    fn part2(&self, input: &BusProbInput) -> Result<u64, Error> {
+        second(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -189,4 +215,18 @@ mod tests {
         let result = parse(&example()).unwrap();
         assert_eq!(result, BusProbInput{earliest_departure_timestamp: 939, in_service_bus_ids: vec!(7,13,1,1,59,1,31,19)});
     }
+
+    #[test]
+    fn test_parse_invalid_bus_id() {
+        let input = vec!(String::from("939"), String::from("7,13,y,59"));
+        let result = parse(&input).unwrap_err();
+        assert_eq!(result.to_string(), "invalid bus id entry y (line 2, column 6)");
+    }
+
+    #[test]
+    fn test_solution() {
+        let input = Day13.parse(&example().join("\n")).unwrap();
+        assert_eq!(Day13.part1(&input).unwrap(), 295);
+        assert_eq!(Day13.part2(&input).unwrap(), 1068781);
+    }
 }
\ No newline at end of file
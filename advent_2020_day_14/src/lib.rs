@@ -1,4 +1,4 @@
-use advent_2020_common::Error;
+use advent_2020_common::{to_lines, Context, Error, Solution};
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::collections::HashMap;
@@ -20,7 +20,7 @@ fn parse_mask(input: &str) -> Result<(u64, u64), Error> {
                 new_and_mask = new_and_mask ^ (1 << (35-i));
             },
             '0' => new_and_mask = new_and_mask ^ (1 << (35-i)),
-            _ => return Error::new("Unknown char in bitmask")
+            _ => return Err(Error::parse(format!("Unknown char in bitmask {}", c)))
         }
     }
 
@@ -110,7 +110,7 @@ fn parse_mask_part_2(input: &str) -> Result<MaskedAddressUpdateIteratorBuilder,
                 and_mask = and_mask ^ (1 << (35-i));
             },
             '0' => continue, // ignored
-            _ => return Error::new("Unknown char in bitmask")
+            _ => return Err(Error::parse(format!("Unknown char in bitmask {}", c)))
         }
     }
 
@@ -122,14 +122,14 @@ pub fn first(input: &[String]) -> Result<u64, Error> {
     let mut and_mask: u64 = 0;
     let mut mem: HashMap<u64, u64> = HashMap::new();
 
-    for line in input {
+    for (i, line) in input.iter().enumerate() {
         if let Some(captures) = MASK_INSTRUCTION_REGEX.captures(line) {
-            let parsed = parse_mask(&captures[1])?;
+            let parsed = parse_mask(&captures[1]).at_line(i + 1)?;
             add_mask = parsed.1;
             and_mask = parsed.0;
         } else if let Some(captures) = UPDATE_INSTRUCTION_REGEX.captures(line) {
-            let mem_loc: u64 = captures[1].parse().or(Error::new("can't parse mem loc"))?;
-            let set_value: u64 = captures[2].parse().or(Error::new("can't parse new value"))?;
+            let mem_loc: u64 = captures[1].parse().at_line(i + 1).context("can't parse mem loc")?;
+            let set_value: u64 = captures[2].parse().at_line(i + 1).context("can't parse new value")?;
 
             mem.insert(mem_loc, (set_value & and_mask) + add_mask);
         }
@@ -142,13 +142,13 @@ pub fn second(input: &[String]) -> Result<u64, Error> {
     let mut mask_builder = MaskedAddressUpdateIteratorBuilder::null();
     let mut mem: HashMap<u64, u64> = HashMap::new();
 
-    for line in input {
+    for (i, line) in input.iter().enumerate() {
         if let Some(captures) = MASK_INSTRUCTION_REGEX.captures(line) {
-            let parsed = parse_mask_part_2(&captures[1])?;
+            let parsed = parse_mask_part_2(&captures[1]).at_line(i + 1)?;
             mask_builder = parsed;
         } else if let Some(captures) = UPDATE_INSTRUCTION_REGEX.captures(line) {
-            let mem_loc: u64 = captures[1].parse().or(Error::new("can't parse mem loc"))?;
-            let set_value: u64 = captures[2].parse().or(Error::new("can't parse new value"))?;
+            let mem_loc: u64 = captures[1].parse().at_line(i + 1).context("can't parse mem loc")?;
+            let set_value: u64 = captures[2].parse().at_line(i + 1).context("can't parse new value")?;
             let mem_loc_iter = mask_builder.build(mem_loc);
             for mem_loc in mem_loc_iter {
                 mem.insert(mem_loc, set_value);
@@ -159,6 +159,26 @@ pub fn second(input: &[String]) -> Result<u64, Error> {
     Ok(mem.iter().fold(0 as u64, |sum, (_, v)| sum + v))
 }
 
+pub struct Day14;
+
+impl Solution for Day14 {
+    type Input = Vec<String>;
+    type First = u64;
+    type Second = u64;
+
+    fn parse(&self, input: &str) -> Result<Vec<String>, Error> {
+        Ok(to_lines(input))
+    }
+
+    fn part1(&self, input: &Vec<String>) -> Result<u64, Error> {
+        first(input)
+    }
+
+    fn part2(&self, input: &Vec<String>) -> Result<u64, Error> {
+        second(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -215,4 +235,13 @@ mod tests {
         let result: Vec<u64> = builder.build(26).collect();
         assert_eq!(result, vec!(16, 24, 18, 26, 17, 25, 19, 27));
     }
+
+    #[test]
+    fn test_solution() {
+        let input = Day14.parse(&example().join("\n")).unwrap();
+        assert_eq!(Day14.part1(&input).unwrap(), 165);
+
+        let input = Day14.parse(&example2().join("\n")).unwrap();
+        assert_eq!(Day14.part2(&input).unwrap(), 208);
+    }
 }
\ No newline at end of file
@@ -1,4 +1,4 @@
-use advent_2020_common::Error;
+use advent_2020_common::{csv_to_nums, Error, Solution};
 use std::collections::HashMap;
 
 pub fn first(input: &[u32]) -> Result<u32, Error> {
@@ -36,6 +36,27 @@ fn spoken_word_game(input: &[u32], iterations: usize) -> Result<u32, Error> {
     Ok(previous as u32)
 }
 
+pub struct Day15;
+
+impl Solution for Day15 {
+    type Input = Vec<u32>;
+    type First = u32;
+    type Second = u32;
+
+    fn parse(&self, input: &str) -> Result<Vec<u32>, Error> {
+        let first_line = input.lines().next().unwrap_or("");
+        csv_to_nums(first_line, 1)
+    }
+
+    fn part1(&self, input: &Vec<u32>) -> Result<u32, Error> {
+        first(input)
+    }
+
+    fn part2(&self, input: &Vec<u32>) -> Result<u32, Error> {
+        second(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -55,4 +76,11 @@ mod tests {
         let result = second(&example()).unwrap();
         assert_eq!(result, 175594)
     }
+
+    #[test]
+    fn test_solution() {
+        let input = Day15.parse("0,3,6").unwrap();
+        assert_eq!(input, example());
+        assert_eq!(Day15.part1(&input).unwrap(), 436);
+    }
 }
\ No newline at end of file
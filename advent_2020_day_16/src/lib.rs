@@ -1,4 +1,4 @@
-use advent_2020_common::Error;
+use advent_2020_common::{csv_to_nums, to_lines, Context, Error, Solution};
 use lazy_static::lazy_static;
 use regex::Regex;
 use itertools::{Chunk, Itertools};
@@ -39,14 +39,14 @@ pub fn first(input: &[String]) -> Result<u32, Error> {
     let mut state = ParseState::FieldDefinitions;
     let mut potentially_valid_ranges: Vec<(u32, u32)> = Vec::new();
 
-    for line in input.iter() {
+    for (line_index, line) in input.iter().enumerate() {
         match state {
             ParseState::FieldDefinitions => { 
                 if let Some(captures) = FIELD_DEFINITION_REGEX.captures(line) {
-                    let range_start_1: u32 = captures[2].parse().or(Error::new("not an int"))?;
-                    let range_end_1: u32 = captures[3].parse().or(Error::new("not an int"))?;
-                    let range_start_2: u32 = captures[4].parse().or(Error::new("not an int"))?;
-                    let range_end_2: u32 = captures[5].parse().or(Error::new("not an int"))?;
+                    let range_start_1: u32 = captures[2].parse().at_line(line_index + 1)?;
+                    let range_end_1: u32 = captures[3].parse().at_line(line_index + 1)?;
+                    let range_start_2: u32 = captures[4].parse().at_line(line_index + 1)?;
+                    let range_end_2: u32 = captures[5].parse().at_line(line_index + 1)?;
 
                     for (range_start, range_end) in vec!((range_start_1, range_end_1), (range_start_2, range_end_2)).iter() {
                         // check if the given range intersects an existing one, in which case we can just grow it
@@ -83,8 +83,9 @@ pub fn first(input: &[String]) -> Result<u32, Error> {
                 }
                 // ignore 'your ticket' for now
             },
+            ParseState::NearbyTickets if line.is_empty() => (),
             ParseState::NearbyTickets => { 
-                let nums: Vec<u32> = line.split(",").map(|s| s.parse().or(Error::new("not an int"))).flatten().collect();
+                let nums: Vec<u32> = csv_to_nums(line, line_index + 1)?;
                 for num in nums {
                     let mut found = false;
                     for valid_range in &potentially_valid_ranges {
@@ -120,16 +121,16 @@ pub fn second(input: &[String]) -> Result<u64, Error> {
     let mut valid_tickets: Vec<Vec<u32>> = Vec::new();
     let mut field_definitions: Vec<FieldDefinition> = Vec::new();
 
-    for line in input.iter() {
+    for (line_index, line) in input.iter().enumerate() {
         match state {
             ParseState::FieldDefinitions => { 
                 if let Some(captures) = FIELD_DEFINITION_REGEX.captures(line) {
                     let field_name: &str = &captures[1];
                     let is_departure = field_name.contains("departure");
-                    let range_start_1: u32 = captures[2].parse().or(Error::new("not an int"))?;
-                    let range_end_1: u32 = captures[3].parse().or(Error::new("not an int"))?;
-                    let range_start_2: u32 = captures[4].parse().or(Error::new("not an int"))?;
-                    let range_end_2: u32 = captures[5].parse().or(Error::new("not an int"))?;
+                    let range_start_1: u32 = captures[2].parse().at_line(line_index + 1)?;
+                    let range_end_1: u32 = captures[3].parse().at_line(line_index + 1)?;
+                    let range_start_2: u32 = captures[4].parse().at_line(line_index + 1)?;
+                    let range_end_2: u32 = captures[5].parse().at_line(line_index + 1)?;
 
                     field_definitions.push(FieldDefinition{is_departure, range_start_1, range_end_1, range_start_2, range_end_2});
 
@@ -166,13 +167,12 @@ pub fn second(input: &[String]) -> Result<u64, Error> {
                 if line == "nearby tickets:" {
                     state = ParseState::NearbyTickets;
                 } else if line.len() > 0 {
-                    for num in line.split(",").map(|s| s.parse().or(Error::new("not an int"))).flatten() {
-                        your_ticket.push(num);
-                    }
+                    your_ticket = csv_to_nums(line, line_index + 1)?;
                 }
             },
+            ParseState::NearbyTickets if line.is_empty() => (),
             ParseState::NearbyTickets => { 
-                let nums: Vec<u32> = line.split(",").map(|s| s.parse().or(Error::new("not an int"))).flatten().collect();
+                let nums: Vec<u32> = csv_to_nums(line, line_index + 1)?;
                 let mut valid = true;
                 for num in nums.iter() {
                     let mut found = false;
@@ -242,7 +242,7 @@ pub fn second(input: &[String]) -> Result<u64, Error> {
                 except_arr.push(except_index);
             }
         } else {
-            return Error::new("Ran out of valid tickets while trying to find field order");
+            return Err(Error::no_solution("Ran out of valid tickets while trying to find field order"));
         }
     }
 
@@ -269,6 +269,26 @@ fn remove_known_field_slots(possible_fields_by_location: &mut Vec<Vec<usize>>, e
     }
 }
 
+pub struct Day16;
+
+impl Solution for Day16 {
+    type Input = Vec<String>;
+    type First = u32;
+    type Second = u64;
+
+    fn parse(&self, input: &str) -> Result<Vec<String>, Error> {
+        Ok(to_lines(input))
+    }
+
+    fn part1(&self, input: &Vec<String>) -> Result<u32, Error> {
+        first(input)
+    }
+
+    fn part2(&self, input: &Vec<String>) -> Result<u64, Error> {
+        second(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -313,6 +333,14 @@ mod tests {
         assert_eq!(result, 71)
     }
 
+    #[test]
+    fn test_first_invalid_ticket() {
+        let mut input = example();
+        input[7] = String::from("40,4x,50");
+        let result = first(&input).unwrap_err();
+        assert_eq!(result.to_string(), "not a number (4x) (line 8, column 4): invalid digit found in string");
+    }
+
     #[test]
     fn test_second() {
         let result = second(&example2()).unwrap();
@@ -333,4 +361,13 @@ mod tests {
         let result = range_overlap((1, 3), (5, 8));
         assert_eq!(result, false);
     }
+
+    #[test]
+    fn test_solution() {
+        let input = Day16.parse(&example().join("\n")).unwrap();
+        assert_eq!(Day16.part1(&input).unwrap(), 71);
+
+        let input = Day16.parse(&example2().join("\n")).unwrap();
+        assert_eq!(Day16.part2(&input).unwrap(), 12 * 13);
+    }
 }
\ No newline at end of file
@@ -1,4 +1,4 @@
-use advent_2020_common::{Error};
+use advent_2020_common::{to_lines, Context, Error, Solution};
 
 #[derive(Debug)]
 enum State {
@@ -10,7 +10,7 @@ enum State {
 pub fn first(input: &[String]) -> Result<u64, Error> {
     let mut accum: u64 = 0;
 
-    for line in input {
+    for (i, line) in input.iter().enumerate() {
         let line = line.replace(')', " ) ").replace('(', " ( ");
 
         let mut stack_values: Vec<(State, u64)> = vec!((State::Initial, 0));
@@ -44,7 +44,7 @@ pub fn first(input: &[String]) -> Result<u64, Error> {
                 }
                 // assume any other tokens are a single digit number
                 x => {
-                    let value: u64 = x.parse().or(Error::from_string(format!("Token not a digit ({})", x)))?;
+                    let value: u64 = x.parse().with_context(|| format!("Token not a digit ({})", x)).at_line(i + 1)?;
                     let new_value = match state {
                         State::Add => current_value + value,
                         State::Multiply => current_value * value,
@@ -119,7 +119,7 @@ fn solve_line_second(tokens: Vec<SecondToken>) -> Result<Vec<SecondToken>, Error
 pub fn second(input: &[String]) -> Result<u64, Error> { 
     let mut sum: u64 = 0;
 
-    for line in input {
+    for (i, line) in input.iter().enumerate() {
         let mut this_level_tokens: Vec<SecondToken> = Vec::new();
 
         let line = line.replace(')', " ) ").replace('(', " ( ");
@@ -146,13 +146,13 @@ pub fn second(input: &[String]) -> Result<u64, Error> {
                     }
                     accum.pop(); // remove trailing ')'
                     accum.pop();
-                    let value = second(&[accum])?;
+                    let value = second(&[accum]).at_line(i + 1)?;
                     this_level_tokens.push(SecondToken::value(value));
                 },
                 "+" => this_level_tokens.push(SecondToken::add()),
                 "*" => this_level_tokens.push(SecondToken::mul()), 
                 x => {
-                    let value: u64 = x.parse().or(Error::from_string(format!("Token not a digit ({})", x)))?;
+                    let value: u64 = x.parse().with_context(|| format!("Token not a digit ({})", x)).at_line(i + 1)?;
                     this_level_tokens.push(SecondToken::value(value));
                 }
             }
@@ -160,7 +160,7 @@ pub fn second(input: &[String]) -> Result<u64, Error> {
 
         let solved_line = solve_line_second(this_level_tokens)?;
         if solved_line.len() != 1 {
-            return Error::new("Multiple value tokens on line without op!?");
+            return Err(Error::parse("Multiple value tokens on line without op!?").at_line(i + 1));
         }
 
         sum += solved_line[0].value;
@@ -169,6 +169,26 @@ pub fn second(input: &[String]) -> Result<u64, Error> {
     Ok(sum)
 }
 
+pub struct Day18;
+
+impl Solution for Day18 {
+    type Input = Vec<String>;
+    type First = u64;
+    type Second = u64;
+
+    fn parse(&self, input: &str) -> Result<Vec<String>, Error> {
+        Ok(to_lines(input))
+    }
+
+    fn part1(&self, input: &Vec<String>) -> Result<u64, Error> {
+        first(input)
+    }
+
+    fn part2(&self, input: &Vec<String>) -> Result<u64, Error> {
+        second(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -229,4 +249,11 @@ mod tests {
         let result = second(&example3()).unwrap();
         assert_eq!(result, 46 + 1445 + 669060 + 23340);
     }
+
+    #[test]
+    fn test_solution() {
+        let input = Day18.parse(&example().join("\n")).unwrap();
+        assert_eq!(Day18.part1(&input).unwrap(), 71);
+        assert_eq!(Day18.part2(&input).unwrap(), 231);
+    }
 }
\ No newline at end of file
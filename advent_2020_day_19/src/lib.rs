@@ -1,4 +1,4 @@
-use advent_2020_common::Error;
+use advent_2020_common::{to_lines, Context, Error, Solution};
 use std::collections::HashMap;
 use regex::{Regex, RegexBuilder};
 
@@ -18,17 +18,17 @@ pub fn parse(input: &[String]) -> Result<Input, Error> {
     let mut rules = HashMap::new(); 
 
     let mut state = ParseState::Rules;
-    for line in input {
+    for (i, line) in input.iter().enumerate() {
         match state {
             _ if line.len() < 1 => state = ParseState::Lines,
             ParseState::Rules => {
                 let mut split = line.split(':');
                 match split.nth(0) {
-                    None => return Error::new("rule without id!?"),
+                    None => return Err(Error::parse("rule without id!?").at_line(i + 1)),
                     Some(id_str) => {
-                        let id: usize = id_str.parse().or(Error::new("not an int"))?;
+                        let id: usize = id_str.parse().at_line(i + 1).context("invalid rule id")?;
                         match split.nth(0) {
-                            None => return Error::new("rule without other part"),
+                            None => return Err(Error::parse("rule without other part").at_line(i + 1)),
                             Some(rule_str) => {
                                 rules.insert(id, String::from(rule_str.trim()));
                             }
@@ -106,10 +106,12 @@ fn process_rule_recur(input: &Input, rule: &String) -> String {
 pub fn first(input: &[String]) -> Result<usize, Error> { 
     let input = parse(&input)?;
 
-    let mut regex_str = process_rule_recur(&input, &input.rules.get(&0).unwrap());
+    let rule_0 = input.rules.get(&0).ok_or_else(|| Error::invalid_input("no rule 0"))?;
+    let mut regex_str = process_rule_recur(&input, rule_0);
     regex_str.insert(0, '^');
     regex_str.push_str("$");
-    let regex = Regex::new(regex_str.as_str()).or(Error::new("invalid regex generated"))?;
+    let regex = Regex::new(regex_str.as_str())
+        .map_err(|e| Error::invalid_input("invalid regex generated").with_source(e))?;
 
     let mut accum = 0;
     for line in input.lines {
@@ -127,14 +129,14 @@ pub fn second(input: &[String]) -> Result<usize, Error> {
     input.rules.insert(8, String::from("42 | 42 8"));
     input.rules.insert(11, String::from("42 31 | 42 11 31"));
 
-    let mut regex_str = process_rule_recur(&input, &input.rules.get(&0).unwrap());
+    let rule_0 = input.rules.get(&0).ok_or_else(|| Error::invalid_input("no rule 0"))?;
+    let mut regex_str = process_rule_recur(&input, rule_0);
     regex_str.insert(0, '^');
     regex_str.push_str("$");
     let regex = RegexBuilder::new(regex_str.as_str())
         .size_limit(10 * (1<<20))
         .build()
-        .unwrap();
-    // let regex = Regex::new(regex_str.as_str()).unwrap(); // .or(Error::new("invalid regex generated"))?;
+        .map_err(|e| Error::invalid_input("invalid regex generated").with_source(e))?;
 
     let mut accum = 0;
     for line in input.lines {
@@ -147,6 +149,26 @@ pub fn second(input: &[String]) -> Result<usize, Error> {
 }
 
 
+pub struct Day19;
+
+impl Solution for Day19 {
+    type Input = Vec<String>;
+    type First = usize;
+    type Second = usize;
+
+    fn parse(&self, input: &str) -> Result<Vec<String>, Error> {
+        Ok(to_lines(input))
+    }
+
+    fn part1(&self, input: &Vec<String>) -> Result<usize, Error> {
+        first(input)
+    }
+
+    fn part2(&self, input: &Vec<String>) -> Result<usize, Error> {
+        second(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -314,4 +336,11 @@ mod tests {
         let expected = "(((a){1}(b){1})|((a){2}(b){2})|((a){3}(b){3})|((a){4}(b){4})|((a){5}(b){5})|((a){6}(b){6})|((a){7}(b){7})|((a){8}(b){8})|((a){9}(b){9}))";
         assert_eq!(result_11, expected);
     }
+
+    #[test]
+    fn test_solution() {
+        let input = Day19.parse(&example3().join("\n")).unwrap();
+        assert_eq!(Day19.part1(&input).unwrap(), 3);
+        assert_eq!(Day19.part2(&input).unwrap(), 12);
+    }
 }
\ No newline at end of file
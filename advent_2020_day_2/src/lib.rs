@@ -1,10 +1,10 @@
-use advent_2020_common::Error;
+use advent_2020_common::{to_lines, Context, Error, Solution};
 
 pub fn first(input: &[String]) -> Result<u32, Error> {
     let mut count = 0; 
 
-    for input in input {
-        let rule = PasswordRule::from_string(input)?;
+    for (i, input) in input.iter().enumerate() {
+        let rule = PasswordRule::from_string(input).at_line(i + 1)?;
         let found_letters: usize = rule.password_to_test.chars()
             .filter(|c| *c == rule.letter)
             .count();
@@ -20,8 +20,8 @@ pub fn first(input: &[String]) -> Result<u32, Error> {
 pub fn second(input: &[String]) -> Result<u32, Error> {
     let mut count = 0; 
 
-    for input in input {
-        let rule = PasswordRule::from_string(input)?;
+    for (i, input) in input.iter().enumerate() {
+        let rule = PasswordRule::from_string(input).at_line(i + 1)?;
         let mut iter = rule.password_to_test.chars();
         let is_rule_letter = |c: &char| *c == rule.letter;
         let found_first = iter.nth(rule.first_num - 1).filter(is_rule_letter);
@@ -70,33 +70,54 @@ impl PasswordRule {
                 0 => {
                     let parts: Vec<&str> = part.split('-').collect();
                     if parts.len() < 2 {
-                        return Error::new("Couldn't parse, int part missing");
+                        return Err(Error::parse("Couldn't parse, int part missing"));
                     }
 
-                    first_num = (&parts[0]).parse().or(Error::new("not an int"))?;
-                    second_num = (&parts[1]).parse().or(Error::new("not an int"))?;
+                    first_num = (&parts[0]).parse()?;
+                    second_num = (&parts[1]).parse()?;
                 }
                 1 => {
-                    letter = part.chars().nth(0).unwrap();
+                    letter = part.chars().nth(0).ok_or_else(|| Error::parse("letter missing"))?;
                 }
                 2 => {
                     password_to_test = String::from(part);
                 }
-                _ => return Error::new("couldn't parse, too many parts!?")
+                _ => return Err(Error::parse("couldn't parse, too many parts!?"))
             }
         }
 
         if !found {
-            Error::new("NYI")
+            Err(Error::parse("empty password rule"))
         } else {
             Ok(PasswordRule{first_num, second_num, letter, password_to_test})
         }
     }
 }
 
+pub struct Day2;
+
+impl Solution for Day2 {
+    type Input = Vec<String>;
+    type First = u32;
+    type Second = u32;
+
+    fn parse(&self, input: &str) -> Result<Vec<String>, Error> {
+        Ok(to_lines(input))
+    }
+
+    fn part1(&self, input: &Vec<String>) -> Result<u32, Error> {
+        first(input)
+    }
+
+    fn part2(&self, input: &Vec<String>) -> Result<u32, Error> {
+        second(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use advent_2020_common::ErrorKind;
 
     fn example() -> Vec<String> {
         let input: Vec<&str> = vec!("1-3 a: abcde", "1-3 b: cdefg", "2-9 c: ccccccccc");
@@ -133,4 +154,20 @@ mod tests {
         };
         assert_eq!(rule, expected);
     }
+
+    #[test]
+    fn test_first_invalid_line() {
+        let mut input = example();
+        input[1] = String::from("1-x b: cdefg");
+        let result = first(&input).unwrap_err();
+        assert_eq!(result.kind(), ErrorKind::Parse);
+        assert_eq!(result.span().map(|s| s.line), Some(2));
+    }
+
+    #[test]
+    fn test_solution() {
+        let input = Day2.parse(&example().join("\n")).unwrap();
+        assert_eq!(Day2.part1(&input).unwrap(), 2);
+        assert_eq!(Day2.part2(&input).unwrap(), 1);
+    }
 }
\ No newline at end of file
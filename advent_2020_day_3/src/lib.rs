@@ -1,4 +1,5 @@
-use advent_2020_common::Error;
+use advent_2020_common::{to_lines, Error, Solution};
+use advent_2020_common::life::{self, Boundary, Topology, X, Y};
 use std::collections::HashSet;
 
 const COL_STEP: usize = 3;
@@ -51,6 +52,8 @@ pub struct SlopeMap {
     trees: HashSet<Coord>,
     max_row: usize, 
     max_col: usize,
+    // the pattern repeats to the right, but not downwards
+    topology: Topology<2>,
 }
 
 impl SlopeMap {
@@ -59,6 +62,7 @@ impl SlopeMap {
             trees: HashSet::<Coord>::new(),
             max_row: input.len() - 1,
             max_col: 0,
+            topology: Topology::unbounded(),
         };
 
         for (row_index, row_str) in input.iter().enumerate() {
@@ -74,11 +78,19 @@ impl SlopeMap {
             }
         }
 
+        map.topology = Topology::new([
+            Boundary::Fixed{min: 0, max: map.max_row as isize},
+            Boundary::Torus{min: 0, max: map.max_col as isize},
+        ]);
+
         Ok(map)
     }
 
     pub fn is_tree(&self, row: usize, col: usize) -> bool {
-        self.trees.contains(&Coord(row, col % (self.max_col() + 1)))
+        match self.topology.resolve(&life::Coord::new_2d(row as isize, col as isize)) {
+            Some(coord) => self.trees.contains(&Coord(coord.at(X) as usize, coord.at(Y) as usize)),
+            None => false,
+        }
     }
 
     pub fn min_row(&self) -> usize { 0 }
@@ -93,6 +105,26 @@ impl SlopeMap {
     }
 }
 
+pub struct Day3;
+
+impl Solution for Day3 {
+    type Input = SlopeMap;
+    type First = usize;
+    type Second = usize;
+
+    fn parse(&self, input: &str) -> Result<SlopeMap, Error> {
+        SlopeMap::new(&to_lines(input))
+    }
+
+    fn part1(&self, input: &SlopeMap) -> Result<usize, Error> {
+        first(input)
+    }
+
+    fn part2(&self, input: &SlopeMap) -> Result<usize, Error> {
+        second(input)
+    }
+}
+
 #[cfg(test)]
 mod tests { 
     use super::*;
@@ -154,5 +186,13 @@ mod tests {
         assert!(map.is_tree(0, map.max_col() + 3)); // loops around
         assert!(map.is_tree(4, 12)); // loop around
         assert!(map.is_tree(2, 6));
+        assert!(!map.is_tree(11, 2)); // off the bottom
+    }
+
+    #[test]
+    fn test_solution() {
+        let input = Day3.parse(&example().join("\n")).unwrap();
+        assert_eq!(Day3.part1(&input).unwrap(), 7);
+        assert_eq!(Day3.part2(&input).unwrap(), 336);
     }
 }
\ No newline at end of file
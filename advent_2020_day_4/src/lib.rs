@@ -1,7 +1,7 @@
 extern crate lazy_static;
 
 use std::collections::HashSet;
-use advent_2020_common::Error;
+use advent_2020_common::{records, to_lines, Error, Solution};
 use lazy_static::lazy_static;
 use regex::Regex;
 
@@ -44,19 +44,11 @@ pub fn second(input: &[String]) -> Result<u32, Error> {
 }
 
 fn count_valid_passports(input: &[String], validate: bool) -> Result<u32, Error> {
-    let mut valid_count = 0;
-    let mut current_start = 0;
-    
-    for (i, input_line) in input.iter().enumerate() {
-        if input_line.len() == 0 {
-            if let Ok(_) = PassportFields::new(&input[current_start .. i], validate) {
-                valid_count = valid_count + 1;
-            }
-            current_start = i;
-        }
-    }
+    let valid_count = records(input)
+        .filter(|passport| PassportFields::new(passport, validate).is_ok())
+        .count();
 
-    Ok(valid_count)
+    Ok(valid_count as u32)
 }
 
 #[derive(Debug, PartialEq)]
@@ -81,11 +73,11 @@ struct PassportFields {
 fn extract_number(input: &str, field_name: &str, min: u32, max: u32, required_digits: Option<usize>) -> Result<u32, Error> {
     match input {
         v if required_digits.is_some() && v.len() != required_digits.unwrap() => {
-            Error::from_string(format!("{} not {} digits: ({})", field_name, required_digits.unwrap(), v))
+            Err(Error::invalid_input(format!("{} not {} digits: ({})", field_name, required_digits.unwrap(), v)))
         },
         v => match v.parse::<u32>() {
-            Err(_) => Error::from_string(format!("{} not a number", field_name)),
-            Ok(v) if v < min || max < v => Error::from_string(format!("{} not in range {} - {}", field_name, min, max)),
+            Err(e) => Err(Error::parse(format!("{} not a number", field_name)).with_source(e)),
+            Ok(v) if v < min || max < v => Err(Error::invalid_input(format!("{} not in range {} - {}", field_name, min, max))),
             Ok(v) => Ok(v)
         },
     }
@@ -143,10 +135,10 @@ impl PassportFields {
                                             let measurement = extract_number(measurement, field_name, 150, 193, None)?;
                                             Height::Centimeters(measurement)
                                         },
-                                        _ => return Error::new("height not in inches nor centimeres"),
+                                        _ => return Err(Error::invalid_input("height not in inches nor centimeres")),
                                     };
                                 },
-                                _ => return Error::new("height not formatted like (num)(units)")
+                                _ => return Err(Error::invalid_input("height not formatted like (num)(units)"))
                             }
                         }
                     },
@@ -154,7 +146,7 @@ impl PassportFields {
                         found_fields.insert(String::from(field_name));
                         if validate {
                             if !HAIR_COLOR_REGEX.is_match(&field_value) {
-                                return Error::new("hair color doesn't match format #[a-f0-9]{6}");
+                                return Err(Error::invalid_input("hair color doesn't match format #[a-f0-9]{6}"));
                             }
                             hair_color = field_value;
                         }
@@ -164,7 +156,7 @@ impl PassportFields {
                         if validate {
                             if !EYE_COLORS.contains(&field_value[..]) {
                                 let valid_colors: Vec<&str> = EYE_COLORS.iter().map(|s| *s).collect();
-                                return Error::from_string(format!("eye color must be one of {:?}", valid_colors));
+                                return Err(Error::invalid_input(format!("eye color must be one of {:?}", valid_colors)));
                             }
                             eye_color = field_value;
                         }
@@ -173,7 +165,7 @@ impl PassportFields {
                         found_fields.insert(String::from(field_name));
                         if validate {
                             if !PASSPORT_ID_REGEX.is_match(&field_value) { 
-                                return Error::new("passport_id doesn't match format [0-9]{6}");
+                                return Err(Error::invalid_input("passport_id doesn't match format [0-9]{9}"));
                             }
                             passport_id = field_value;
                         }
@@ -184,7 +176,7 @@ impl PassportFields {
         }
 
         return if found_fields.len() != 7 {
-            Error::from_string(format!("Not all fields present! {}", found_fields.len()))
+            Err(Error::invalid_input(format!("Not all fields present! {}", found_fields.len())))
         } else {
             Ok(PassportFields{
                 birth_year, issue_year, expiration_year, height,
@@ -196,6 +188,26 @@ impl PassportFields {
     }
 }
 
+pub struct Day4;
+
+impl Solution for Day4 {
+    type Input = Vec<String>;
+    type First = u32;
+    type Second = u32;
+
+    fn parse(&self, input: &str) -> Result<Vec<String>, Error> {
+        Ok(to_lines(input))
+    }
+
+    fn part1(&self, input: &Vec<String>) -> Result<u32, Error> {
+        first(input)
+    }
+
+    fn part2(&self, input: &Vec<String>) -> Result<u32, Error> {
+        second(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -265,6 +277,16 @@ mod tests {
         assert_eq!(result, expected)
     }
 
+    #[test]
+    fn test_first_no_final_separator() {
+        let mut input = example_invalid();
+        input.pop();
+        input.push(String::from(""));
+        input.extend(example().into_iter().take(2));
+        let result = first(&input).unwrap();
+        assert_eq!(result, 2);
+    }
+
     #[test]
     fn test_second() { 
         let result = second(&example_mixed()).unwrap();
@@ -311,4 +333,11 @@ mod tests {
         let result = PassportFields::new(&input, true);
         assert!(result.is_err());
     }
-}
+
+    #[test]
+    fn test_solution() {
+        let input = Day4.parse(&example_mixed().join("\n")).unwrap();
+        assert_eq!(Day4.part1(&input).unwrap(), 8);
+        assert_eq!(Day4.part2(&input).unwrap(), 4);
+    }
+}
\ No newline at end of file
@@ -1,11 +1,6 @@
-use advent_2020_common::{input, Error};
-use advent_2020_day_4::{first, second};
+use advent_2020_common::{day_main, Error};
+use advent_2020_day_4::Day4;
 
 fn main() -> Result<(), Error> {
-    let input = input().or(Error::new("Couldn't read input file"))?;
-    println!("Using puzzle input {:?}", input);
-
-    println!("First output: {}", first(&input).unwrap());
-    println!("Second output: {}", second(&input).unwrap());
-    Ok(())
+    day_main(4, &Day4)
 }
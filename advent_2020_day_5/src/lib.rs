@@ -1,11 +1,11 @@
-use advent_2020_common::Error;
+use advent_2020_common::{to_lines, Context, Error, Solution};
 
 struct SeatId(usize);
 
 pub fn first(input: &[String]) -> Result<usize, Error> {
     let mut max = 0;
-    for input in input {
-        let seat_id = SeatId::new(input)?;
+    for (i, input) in input.iter().enumerate() {
+        let seat_id = SeatId::new(input).at_line(i + 1)?;
         if seat_id.0 > max { 
             max = seat_id.0;
         }
@@ -30,13 +30,13 @@ pub fn second(input: &[String]) -> Result<usize, Error> {
         last_i = Some(seat_id);
     }
 
-    Error::new("Not found")
+    Err(Error::no_solution("no gap in the seat ids"))
 }
 
 impl SeatId {
     fn new(input: &String) -> Result<SeatId, Error> {
         if input.len() != 10 {
-            return Error::new("wrong sized input");
+            return Err(Error::parse("wrong sized input"));
         }
 
         let mut cs = input.chars();
@@ -54,12 +54,32 @@ fn binary_from_chars(input: &mut dyn Iterator<Item = char>, count: usize, front_
         match c {
             c if c == back_char => accum = accum + (1 << (count-i - 1)),
             c if c == front_char => (),
-            _ => return Error::from_string(format!("Unknown F/B character: {}", c))
+            _ => return Err(Error::parse(format!("Unknown {}/{} character: {}", front_char, back_char, c)))
         }
     }
     Ok(accum)
 }
 
+pub struct Day5;
+
+impl Solution for Day5 {
+    type Input = Vec<String>;
+    type First = usize;
+    type Second = usize;
+
+    fn parse(&self, input: &str) -> Result<Vec<String>, Error> {
+        Ok(to_lines(input))
+    }
+
+    fn part1(&self, input: &Vec<String>) -> Result<usize, Error> {
+        first(input)
+    }
+
+    fn part2(&self, input: &Vec<String>) -> Result<usize, Error> {
+        second(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -96,4 +116,11 @@ mod tests {
         let expected = 44;
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn test_solution() {
+        let input: Vec<String> = examples().iter().map(|(e, _)| e.clone()).collect();
+        let input = Day5.parse(&input.join("\n")).unwrap();
+        assert_eq!(Day5.part1(&input).unwrap(), 820);
+    }
 }
\ No newline at end of file
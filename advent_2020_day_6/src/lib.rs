@@ -1,5 +1,5 @@
 use std::collections::HashMap;
-use advent_2020_common::Error;
+use advent_2020_common::{records, to_lines, Error, Solution};
 
 pub fn first(input: &[String]) -> Result<usize, Error> {
     count_questions(input, false)
@@ -11,32 +11,41 @@ pub fn second(input:&[String]) -> Result<usize, Error> {
 
 fn count_questions(input: &[String], require_all: bool) -> Result<usize, Error> {
     let mut question_count = 0;
-    let mut current_start = 0;
-    let mut current_question_yesses = HashMap::<char, usize>::new();
-
-    for (i, input_line) in input.iter().enumerate() {
-        if input_line.len() == 0 {
-            for (_question_name, count) in current_question_yesses.iter() {
-                if !require_all || (*count == (i - current_start)) {
-                    question_count = question_count + 1;
-                }
-            }
-            current_question_yesses.clear();
-            current_start = i + 1;
-        } else {
-            for c in input_line.chars() {
-                let next_count = current_question_yesses.get(&c)
-                    .map(|current| current + 1)
-                    .unwrap_or(1);
-                    
-                current_question_yesses.insert(c, next_count);
-            }
+
+    for group in records(input) {
+        let mut current_question_yesses = HashMap::<char, usize>::new();
+        for c in group.iter().flat_map(|person| person.chars()) {
+            *current_question_yesses.entry(c).or_insert(0) += 1;
         }
+
+        question_count += current_question_yesses.values()
+            .filter(|count| !require_all || **count == group.len())
+            .count();
     }
 
     Ok(question_count)
 }
 
+pub struct Day6;
+
+impl Solution for Day6 {
+    type Input = Vec<String>;
+    type First = usize;
+    type Second = usize;
+
+    fn parse(&self, input: &str) -> Result<Vec<String>, Error> {
+        Ok(to_lines(input))
+    }
+
+    fn part1(&self, input: &Vec<String>) -> Result<usize, Error> {
+        first(input)
+    }
+
+    fn part2(&self, input: &Vec<String>) -> Result<usize, Error> {
+        second(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -75,4 +84,19 @@ mod tests {
         let expected = 6;
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn test_no_final_separator() {
+        let mut input = example();
+        input.pop();
+        assert_eq!(first(&input).unwrap(), 11);
+        assert_eq!(second(&input).unwrap(), 6);
+    }
+
+    #[test]
+    fn test_solution() {
+        let input = Day6.parse(&example().join("\n")).unwrap();
+        assert_eq!(Day6.part1(&input).unwrap(), 11);
+        assert_eq!(Day6.part2(&input).unwrap(), 6);
+    }
 }
\ No newline at end of file
@@ -1,5 +1,5 @@
 use std::collections::{HashSet, HashMap, VecDeque};
-use advent_2020_common::Error;
+use advent_2020_common::{to_lines, Context, Error, Solution};
 extern crate lazy_static;
 
 use lazy_static::lazy_static;
@@ -59,10 +59,10 @@ impl DirectedBagsGraph {
             }
         };
 
-        for line in input.iter() {
+        for (line_index, line) in input.iter().enumerate() {
             let mut captures = BAG_GRAPH_REGEX.captures_iter(line).peekable();
             if captures.peek().is_none() { 
-                return Error::from_string(format!("invalid line {}", line));
+                return Err(Error::parse(format!("invalid line {}", line)).at_line(line_index + 1));
             }
            
             let mut to_index = 0;
@@ -75,8 +75,9 @@ impl DirectedBagsGraph {
                         let from_index = existing_index_or_insert(&capture[2]);
                         let count: usize = capture.get(1)
                             .map(|s| s.as_str().trim()).unwrap_or("")
-                            .parse()
-                            .or(Error::new("couldn't parse count"))?;
+                            .parse::<usize>()
+                            .at_line(line_index + 1)
+                            .context("couldn't parse count")?;
                         if invert {
                             insert_edge(to_index, Edge{to: from_index, count});
                         } else {
@@ -102,7 +103,8 @@ impl DirectedBagsGraph {
 struct DfsStep{vertex_id: usize, multiplier: usize}
 
 fn depth_first_search(graph: &DirectedBagsGraph, starting_bag: &String) -> Result<(usize, usize), Error> {
-    let start_vertex_id = graph.vertex_id_named(starting_bag).unwrap();
+    let start_vertex_id = graph.vertex_id_named(starting_bag)
+        .ok_or_else(|| Error::invalid_input(format!("no rule for {} bags", starting_bag)))?;
     let mut queue = VecDeque::new();
     queue.push_back(DfsStep{vertex_id: start_vertex_id, multiplier: 1});
     let mut seen: HashSet<usize> = HashSet::new();
@@ -132,6 +134,36 @@ pub fn second(graph: &DirectedBagsGraph, target_bag: &String) -> Result<usize, E
     depth_first_search(graph, target_bag).map(|r| r.1)
 }
 
+pub struct Day7 {
+    pub target_bag: String,
+}
+
+impl Default for Day7 {
+    fn default() -> Self {
+        Day7{target_bag: String::from("shiny gold")}
+    }
+}
+
+impl Solution for Day7 {
+    // part 1 walks from a bag to the bags that contain it, part 2 from a bag to its contents
+    type Input = (DirectedBagsGraph, DirectedBagsGraph);
+    type First = usize;
+    type Second = usize;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        let lines = to_lines(input);
+        Ok((DirectedBagsGraph::new(&lines, false)?, DirectedBagsGraph::new(&lines, true)?))
+    }
+
+    fn part1(&self, input: &Self::Input) -> Result<usize, Error> {
+        first(&input.0, &self.target_bag)
+    }
+
+    fn part2(&self, input: &Self::Input) -> Result<usize, Error> {
+        second(&input.1, &self.target_bag)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -255,4 +287,12 @@ mod tests {
         }).next().unwrap().count;
         assert_eq!(vibrant_plum_edge, 2);
     }
+
+    #[test]
+    fn test_solution() {
+        let day = Day7::default();
+        let input = day.parse(&example().join("\n")).unwrap();
+        assert_eq!(day.part1(&input).unwrap(), 4);
+        assert_eq!(day.part2(&input).unwrap(), 32);
+    }
 }
\ No newline at end of file
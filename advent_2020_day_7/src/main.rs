@@ -1,13 +1,6 @@
-use advent_2020_common::{input, Error};
-use advent_2020_day_7::{first, second, DirectedBagsGraph};
+use advent_2020_common::{day_main, Error};
+use advent_2020_day_7::Day7;
 
 fn main() -> Result<(), Error> {
-    let input = input().or(Error::new("Couldn't read input file"))?;
-    println!("Using puzzle input {:?}", input);
-
-    let input1 = DirectedBagsGraph::new(&input, false).unwrap();
-    println!("First output: {}", first(&input1, &String::from("shiny gold")).unwrap());
-    let input2 = DirectedBagsGraph::new(&input, true).unwrap();
-    println!("Second output: {}", second(&input2, &String::from("shiny gold")).unwrap());
-    Ok(())
+    day_main(7, &Day7::default())
 }
@@ -1,4 +1,4 @@
-use advent_2020_common::Error;
+use advent_2020_common::{to_lines, Context, Error, Solution};
 extern crate lazy_static;
 
 use lazy_static::lazy_static;
@@ -33,8 +33,9 @@ impl MakesInstructions for Instructions {
     fn from_strings(input: &[String]) -> Result<Instructions, Error> {
         let mut instructions = Instructions::new();
 
-        for instruction_str in input.iter() {
-            let captures = INSTRUCTION_REGEX.captures(instruction_str).ok_or(Error{msg: String::from("didn't match rege")})?;
+        for (i, instruction_str) in input.iter().enumerate() {
+            let captures = INSTRUCTION_REGEX.captures(instruction_str)
+                .ok_or_else(|| Error::parse(format!("not an instruction ({})", instruction_str)).at_line(i + 1))?;
 
             let op_str: Option<&str> = captures.get(1).map(|m| m.as_str());
             
@@ -42,12 +43,12 @@ impl MakesInstructions for Instructions {
                 Some("nop") => Operation::None,
                 Some("acc") => Operation::Accumulate,
                 Some("jmp") => Operation::Jump,
-                _ => return Error::new("missing instruction!"),
+                _ => return Err(Error::parse("missing instruction!").at_line(i + 1)),
             };
 
             let amount: isize = captures.get(2)
-                .map(|m| m.as_str()).ok_or(Error{msg: String::from("missing amount")})?
-                .parse().or(Error::new("missing amount"))?;
+                .map(|m| m.as_str()).ok_or_else(|| Error::parse("missing amount").at_line(i + 1))?
+                .parse().at_line(i + 1)?;
 
 
             instructions.push(Instruction{op, amount});
@@ -106,7 +107,7 @@ fn process_instructions(instructions: &Instructions) -> Result<ProcessResult, Er
             Operation::Jump => {
                 let next_prog: isize = (prog as isize) + instruction.amount;
                 if next_prog < 0 {
-                    return Error::new("program counter went below 0!");
+                    return Err(Error::invalid_input("program counter went below 0!").at_line(prog + 1));
                 }
                 prog = next_prog as usize;
             }
@@ -117,7 +118,7 @@ fn process_instructions(instructions: &Instructions) -> Result<ProcessResult, Er
 pub fn first(input: &[String]) -> Result<isize, Error> {
     let instructions = Instructions::from_strings(input)?;
     match process_instructions(&instructions) {
-        Ok(ProcessResult::Terminated(_)) => Error::new("program counter ran off available instructions"),
+        Ok(ProcessResult::Terminated(_)) => Err(Error::no_solution("program counter ran off available instructions")),
         Ok(ProcessResult::InfiniteLoop(accum)) => Ok(accum),
         Err(x) => Err(x),
     }
@@ -143,7 +144,27 @@ pub fn second(input: &[String]) -> Result<isize, Error> {
         }
     }
     
-    Error::new("Not found!")
+    Err(Error::no_solution("no single swapped instruction makes the program terminate"))
+}
+
+pub struct Day8;
+
+impl Solution for Day8 {
+    type Input = Vec<String>;
+    type First = isize;
+    type Second = isize;
+
+    fn parse(&self, input: &str) -> Result<Vec<String>, Error> {
+        Ok(to_lines(input))
+    }
+
+    fn part1(&self, input: &Vec<String>) -> Result<isize, Error> {
+        first(input)
+    }
+
+    fn part2(&self, input: &Vec<String>) -> Result<isize, Error> {
+        second(input)
+    }
 }
 
 #[cfg(test)]
@@ -215,4 +236,11 @@ mod tests {
         let str_version = orig.to_strings();
         assert_eq!(input, str_version);
     }
+
+    #[test]
+    fn test_solution() {
+        let input = Day8.parse(&example().join("\n")).unwrap();
+        assert_eq!(Day8.part1(&input).unwrap(), 5);
+        assert_eq!(Day8.part2(&input).unwrap(), 8);
+    }
 }
\ No newline at end of file
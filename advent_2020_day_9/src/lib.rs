@@ -1,23 +1,27 @@
-use advent_2020_common::{find_complements, Error};
+use advent_2020_common::{find_complements, input_to_nums, to_lines, Error, Solution};
 use std::cmp::Ordering;
 
-pub fn first(input: &Vec<u32>, prelude_size: usize) -> Result<usize, Error> {
+pub fn first(input: &Vec<u64>, prelude_size: usize) -> Result<u64, Error> {
     for (index, num) in input.iter().enumerate() {
         if index < prelude_size {
             continue;
         }
 
-        let range: &[u32] = &input.as_slice()[(index - prelude_size)..index];
+        let range: &[u64] = &input.as_slice()[(index - prelude_size)..index];
         match find_complements(range, *num, false) {
             Some(_) => (),
-            _ => return Ok(*num as usize),
+            _ => return Ok(*num),
         }
     }
 
-    Error::new("Not found!")
+    Err(Error::no_solution("every number is a sum of two in its prelude"))
 }
 
-pub fn second(input: &Vec<u32>, target_num: u32) -> Result<u32, Error> {
+pub fn second(input: &Vec<u64>, target_num: u64) -> Result<u64, Error> {
+    if input.len() < 2 {
+        return Err(Error::invalid_input("need at least two numbers"));
+    }
+
     let mut start_range = 0;
     let mut end_range = 1; // must be at least two numbers in the range
     let mut current_sum = input[0] + input[1];
@@ -32,6 +36,9 @@ pub fn second(input: &Vec<u32>, target_num: u32) -> Result<u32, Error> {
         let compare = current_sum.cmp(&target_num);
         match compare {
             Ordering::Less => {
+                if end_range + 1 == input.len() {
+                    return Err(Error::no_solution(format!("no contiguous range sums to {}", target_num)));
+                }
                 end_range += 1;
                 current_sum += input[end_range];
             },
@@ -40,7 +47,7 @@ pub fn second(input: &Vec<u32>, target_num: u32) -> Result<u32, Error> {
                 start_range += 1;
             },
             Ordering::Equal => {
-                let mut min = u32::MAX;
+                let mut min = u64::MAX;
                 let mut max = 0;
                 for i in start_range ..= end_range {
                     if input[i] < min {
@@ -86,6 +93,35 @@ pub fn second(input: &Vec<u32>, target_num: u32) -> Result<u32, Error> {
     //
 }
 
+pub struct Day9 {
+    pub prelude_size: usize,
+}
+
+impl Default for Day9 {
+    fn default() -> Self {
+        Day9{prelude_size: 25}
+    }
+}
+
+impl Solution for Day9 {
+    type Input = Vec<u64>;
+    type First = u64;
+    type Second = u64;
+
+    fn parse(&self, input: &str) -> Result<Vec<u64>, Error> {
+        input_to_nums(&to_lines(input))
+    }
+
+    fn part1(&self, input: &Vec<u64>) -> Result<u64, Error> {
+        first(input, self.prelude_size)
+    }
+
+    fn part2(&self, input: &Vec<u64>) -> Result<u64, Error> {
+        let target_num = first(input, self.prelude_size)?;
+        second(input, target_num)
+    }
+}
+
 #[cfg(test)]
 mod tests{
     use super::*;
@@ -118,13 +154,13 @@ mod tests{
 
     #[test]
     fn test_first() {
-        let result = first(&input_to_nums(&example()), 5).unwrap();
+        let result = first(&input_to_nums(&example()).unwrap(), 5).unwrap();
         assert_eq!(result, 127);
     }
 
     #[test]
     fn test_parse() {
-        let nums = input_to_nums(&example());
+        let nums: Vec<u64> = input_to_nums(&example()).unwrap();
         assert_eq!(nums[0], 35);
         assert_eq!(nums[10], 102);
         assert_eq!(nums[19], 576);
@@ -132,9 +168,17 @@ mod tests{
 
     #[test]
     fn test_second() {
-        let input = &input_to_nums(&example());
+        let input = &input_to_nums(&example()).unwrap();
         let result = first(input, 5).unwrap();
-        let result = second(input, result as u32).unwrap();
+        let result = second(input, result).unwrap();
         assert_eq!(result, 62);
     }
+
+    #[test]
+    fn test_solution() {
+        let day = Day9{prelude_size: 5};
+        let input = day.parse(&example().join("\n")).unwrap();
+        assert_eq!(day.part1(&input).unwrap(), 127);
+        assert_eq!(day.part2(&input).unwrap(), 62);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,363 @@
+use advent_2020_common::{bench::{bench, BenchReport}, fetch::InputProvider, loader::read_source, report::{run, PartResult}, verify::{verify, Answers, PartVerification}, Context, Error, Solution};
+use advent_2020_day_1::Day1;
+use advent_2020_day_2::Day2;
+use advent_2020_day_3::Day3;
+use advent_2020_day_4::Day4;
+use advent_2020_day_5::Day5;
+use advent_2020_day_6::Day6;
+use advent_2020_day_7::Day7;
+use advent_2020_day_8::Day8;
+use advent_2020_day_9::Day9;
+use advent_2020_day_10::Day10;
+use advent_2020_day_11::Day11;
+use advent_2020_day_12::Day12;
+use advent_2020_day_13::Day13;
+use advent_2020_day_14::Day14;
+use advent_2020_day_15::Day15;
+use advent_2020_day_16::Day16;
+use advent_2020_day_17::Day17;
+use advent_2020_day_18::Day18;
+use advent_2020_day_19::Day19;
+
+pub const FIRST_DAY: usize = 1;
+pub const LAST_DAY: usize = 19;
+
+pub const DEFAULT_BENCH_ITERATIONS: usize = 10;
+
+pub const YEAR: u32 = 2020;
+/// Where fetched inputs are cached, relative to the workspace root
+pub const CACHE_DIR: &str = ".aoc_cache";
+
+const USAGE: &str = "usage: aoc run [--day N[,N...]]... [--part 1|2] [--input path] [--json]
+       aoc bench [--day N[,N...]]... [--iterations N] [--input path] [--json]
+       aoc verify [--day N[,N...]]...
+       aoc fetch [--day N[,N...]]...";
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Run(RunArgs),
+    Bench(BenchArgs),
+    /// Check the listed days against their checked in answers.txt
+    Verify(Vec<usize>),
+    /// Fetch the listed days' inputs into any missing input.txt, see `advent_2020_common::fetch`
+    Fetch(Vec<usize>),
+}
+
+/// Which days / parts to run, an optional input file overriding the day's own input.txt,
+/// and whether to print one JSON object per part instead of plain text
+#[derive(Debug, PartialEq)]
+pub struct RunArgs {
+    pub days: Vec<usize>,
+    pub parts: Vec<usize>,
+    pub input: Option<String>,
+    pub json: bool,
+}
+
+/// Which days to benchmark and how, see `advent_2020_common::bench`
+#[derive(Debug, PartialEq)]
+pub struct BenchArgs {
+    pub days: Vec<usize>,
+    pub iterations: usize,
+    pub input: Option<String>,
+    pub json: bool,
+}
+
+/// Parse the runner's command line (not including the program name).
+/// Examples:
+/// ```
+/// use advent_2020_runner::{parse_args, Command, RunArgs};
+/// let args: Vec<String> = vec!("run", "--day", "7", "--part", "2").iter().map(|s| String::from(*s)).collect();
+/// let command = parse_args(&args).unwrap();
+/// assert_eq!(command, Command::Run(RunArgs{days: vec!(7), parts: vec!(2), input: None, json: false}));
+/// ```
+///
+pub fn parse_args(args: &[String]) -> Result<Command, Error> {
+    let mut args = args.iter();
+    let command = match args.next().map(|s| s.as_str()) {
+        Some(x @ "run") | Some(x @ "bench") | Some(x @ "verify") | Some(x @ "fetch") => x,
+        Some(x) => return Err(Error::invalid_input(format!("unknown command {}\n{}", x, USAGE))),
+        None => return Err(Error::invalid_input(USAGE)),
+    };
+
+    let mut days: Vec<usize> = Vec::new();
+    let mut parts: Vec<usize> = Vec::new();
+    let mut iterations = None;
+    let mut input = None;
+    let mut json = false;
+
+    while let Some(arg) = args.next() {
+        if arg == "--json" && (command == "run" || command == "bench") {
+            json = true;
+            continue;
+        }
+
+        let value = match args.next() {
+            Some(v) => v,
+            None => return Err(Error::invalid_input(format!("missing value for {}\n{}", arg, USAGE))),
+        };
+
+        match arg.as_str() {
+            "--day" => {
+                for day_str in value.split(',') {
+                    let day: usize = day_str.trim().parse().with_context(|| format!("invalid day ({})", day_str))?;
+                    if !(FIRST_DAY ..= LAST_DAY).contains(&day) {
+                        return Err(Error::invalid_input(format!("no solution for day {}", day)));
+                    }
+                    days.push(day);
+                }
+            },
+            "--part" if command == "run" => {
+                let part: usize = value.parse().with_context(|| format!("invalid part ({})", value))?;
+                if part != 1 && part != 2 {
+                    return Err(Error::invalid_input(format!("part must be 1 or 2 ({})", part)));
+                }
+                parts.push(part);
+            },
+            "--iterations" if command == "bench" => {
+                let count: usize = value.parse().with_context(|| format!("invalid iterations ({})", value))?;
+                if count == 0 {
+                    return Err(Error::invalid_input("need at least one iteration to benchmark"));
+                }
+                iterations = Some(count);
+            },
+            "--input" if command == "run" || command == "bench" => input = Some(value.clone()),
+            x => return Err(Error::invalid_input(format!("unknown option {}\n{}", x, USAGE))),
+        }
+    }
+
+    if days.is_empty() {
+        days = (FIRST_DAY ..= LAST_DAY).collect();
+    }
+    if parts.is_empty() {
+        parts = vec!(1, 2);
+    }
+    if input.is_some() && days.len() > 1 {
+        return Err(Error::invalid_input("--input can only be used when running a single day"));
+    }
+
+    match command {
+        "bench" => {
+            let iterations = iterations.unwrap_or(DEFAULT_BENCH_ITERATIONS);
+            Ok(Command::Bench(BenchArgs{days, iterations, input, json}))
+        },
+        "verify" => Ok(Command::Verify(days)),
+        "fetch" => Ok(Command::Fetch(days)),
+        _ => Ok(Command::Run(RunArgs{days, parts, input, json})),
+    }
+}
+
+/// Where a day's checked in puzzle input lives, relative to the workspace root
+pub fn default_input_path(day: usize) -> String {
+    format!("advent_2020_day_{}/input.txt", day)
+}
+
+/// Where a day's known answers for its input.txt live, relative to the workspace root
+pub fn default_answers_path(day: usize) -> String {
+    format!("advent_2020_day_{}/answers.txt", day)
+}
+
+fn read_input(day: usize, input_path: Option<&str>) -> Result<String, Error> {
+    let input_path = input_path.map(String::from).unwrap_or_else(|| default_input_path(day));
+    read_source(&input_path).with_context(|| format!("Couldn't read input file {}", input_path))
+}
+
+/// Read the input for a day and solve the requested parts, returning the answers in part order
+pub fn run_day(day: usize, parts: &[usize], input_path: Option<&str>) -> Result<Vec<String>, Error> {
+    let input = read_input(day, input_path)?;
+    solve(day, parts, &input)
+}
+
+/// Read the input for a day and solve the requested parts, timing each; errors solving a part
+/// are recorded in its result rather than returned
+pub fn report_day(day: usize, parts: &[usize], input_path: Option<&str>) -> Result<Vec<PartResult>, Error> {
+    let input = read_input(day, input_path)?;
+    Ok(day_solution(day)?.report(day, parts, &input))
+}
+
+/// Read the input for a day and benchmark its parse step and both parts
+pub fn bench_day(day: usize, iterations: usize, input_path: Option<&str>) -> Result<BenchReport, Error> {
+    let input = read_input(day, input_path)?;
+    day_solution(day)?.bench(day, &input, iterations)
+}
+
+/// Solve both parts of a day from its own input.txt and compare them against its answers.txt
+pub fn verify_day(day: usize) -> Result<Vec<PartVerification>, Error> {
+    let input = read_input(day, None)?;
+    let answers = Answers::from_file(&default_answers_path(day))?;
+    Ok(day_solution(day)?.verify(day, &input, &answers))
+}
+
+/// Fetch a day's input (or take it from the cache) and write it to the day's input.txt, unless
+/// that already exists.  Returns whether input.txt was written.
+pub fn fetch_day(provider: &InputProvider, day: usize) -> Result<bool, Error> {
+    let input_path = default_input_path(day);
+    if std::path::Path::new(&input_path).exists() {
+        return Ok(false);
+    }
+
+    let input = provider.input(YEAR, day as u32)?;
+    std::fs::write(&input_path, input).with_context(|| format!("Couldn't write input file {}", input_path))?;
+    Ok(true)
+}
+
+/// Solve the requested parts of a day from its raw input text, returning the answers in part order
+pub fn solve(day: usize, parts: &[usize], input: &str) -> Result<Vec<String>, Error> {
+    day_solution(day)?.solve(parts, input)
+}
+
+/// `Solution` with its input / answer types hidden, so every day can be picked at runtime
+trait DaySolution {
+    fn solve(&self, parts: &[usize], input: &str) -> Result<Vec<String>, Error>;
+    fn report(&self, day: usize, parts: &[usize], input: &str) -> Vec<PartResult>;
+    fn bench(&self, day: usize, input: &str, iterations: usize) -> Result<BenchReport, Error>;
+    fn verify(&self, day: usize, input: &str, answers: &Answers) -> Vec<PartVerification>;
+}
+
+impl<S: Solution> DaySolution for S {
+    fn solve(&self, parts: &[usize], input: &str) -> Result<Vec<String>, Error> {
+        let parsed = self.parse(input)?;
+
+        parts.iter()
+            .map(|part| match part {
+                1 => self.part1(&parsed).map(|answer| answer.to_string()),
+                2 => self.part2(&parsed).map(|answer| answer.to_string()),
+                x => Err(Error::invalid_input(format!("no part {}", x))),
+            })
+            .collect()
+    }
+
+    fn report(&self, day: usize, parts: &[usize], input: &str) -> Vec<PartResult> {
+        run(day, self, input, parts)
+    }
+
+    fn bench(&self, day: usize, input: &str, iterations: usize) -> Result<BenchReport, Error> {
+        bench(day, self, input, iterations)
+    }
+
+    fn verify(&self, day: usize, input: &str, answers: &Answers) -> Vec<PartVerification> {
+        verify(day, self, input, answers)
+    }
+}
+
+fn day_solution(day: usize) -> Result<Box<dyn DaySolution>, Error> {
+    let solution: Box<dyn DaySolution> = match day {
+        1 => Box::new(Day1),
+        2 => Box::new(Day2),
+        3 => Box::new(Day3),
+        4 => Box::new(Day4),
+        5 => Box::new(Day5),
+        6 => Box::new(Day6),
+        7 => Box::new(Day7::default()),
+        8 => Box::new(Day8),
+        9 => Box::new(Day9::default()),
+        10 => Box::new(Day10),
+        11 => Box::new(Day11),
+        12 => Box::new(Day12),
+        13 => Box::new(Day13),
+        14 => Box::new(Day14),
+        15 => Box::new(Day15),
+        16 => Box::new(Day16),
+        17 => Box::new(Day17),
+        18 => Box::new(Day18),
+        19 => Box::new(Day19),
+        _ => return Err(Error::invalid_input(format!("no solution for day {}", day))),
+    };
+    Ok(solution)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(input: &[&str]) -> Vec<String> {
+        input.iter().map(|s| String::from(*s)).collect()
+    }
+
+    #[test]
+    fn test_parse_args_defaults() {
+        let result = parse_args(&args(&["run"])).unwrap();
+        let expected = Command::Run(RunArgs{days: (1 ..= 19).collect(), parts: vec!(1, 2), input: None, json: false});
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_parse_args_subset() {
+        let result = parse_args(&args(&["run", "--day", "3,7", "--day", "9", "--part", "1"])).unwrap();
+        let expected = Command::Run(RunArgs{days: vec!(3, 7, 9), parts: vec!(1), input: None, json: false});
+        assert_eq!(result, expected);
+
+        let result = parse_args(&args(&["run", "--json", "--day", "4"])).unwrap();
+        let expected = Command::Run(RunArgs{days: vec!(4), parts: vec!(1, 2), input: None, json: true});
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_parse_args_bench() {
+        let result = parse_args(&args(&["bench", "--day", "15", "--json", "--iterations", "3"])).unwrap();
+        let expected = Command::Bench(BenchArgs{days: vec!(15), iterations: 3, input: None, json: true});
+        assert_eq!(result, expected);
+
+        let result = parse_args(&args(&["bench"])).unwrap();
+        let expected = Command::Bench(BenchArgs{days: (1 ..= 19).collect(), iterations: DEFAULT_BENCH_ITERATIONS, input: None, json: false});
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_parse_args_verify() {
+        let result = parse_args(&args(&["verify", "--day", "1,2"])).unwrap();
+        assert_eq!(result, Command::Verify(vec!(1, 2)));
+
+        let result = parse_args(&args(&["verify"])).unwrap();
+        assert_eq!(result, Command::Verify((1 ..= 19).collect()));
+    }
+
+    #[test]
+    fn test_parse_args_fetch() {
+        let result = parse_args(&args(&["fetch", "--day", "18"])).unwrap();
+        assert_eq!(result, Command::Fetch(vec!(18)));
+
+        let result = parse_args(&args(&["fetch"])).unwrap();
+        assert_eq!(result, Command::Fetch((1 ..= 19).collect()));
+    }
+
+    #[test]
+    fn test_parse_args_invalid() {
+        assert!(parse_args(&args(&[])).is_err());
+        assert!(parse_args(&args(&["run", "--day", "20"])).is_err());
+        assert!(parse_args(&args(&["run", "--part", "3"])).is_err());
+        assert!(parse_args(&args(&["run", "--day"])).is_err());
+        assert!(parse_args(&args(&["run", "--input", "foo.txt"])).is_err());
+        assert!(parse_args(&args(&["verify", "--json"])).is_err());
+        assert!(parse_args(&args(&["bench", "--part", "1"])).is_err());
+        assert!(parse_args(&args(&["bench", "--iterations", "0"])).is_err());
+        assert!(parse_args(&args(&["verify", "--day", "1", "--input", "foo.txt"])).is_err());
+        assert!(parse_args(&args(&["verify", "--part", "1"])).is_err());
+        assert!(parse_args(&args(&["fetch", "--day", "1", "--input", "foo.txt"])).is_err());
+    }
+
+    #[test]
+    fn test_solve() {
+        let input = "1721\n979\n366\n299\n675\n1456";
+        assert_eq!(solve(1, &[1, 2], input).unwrap(), vec!("514579", "241861950"));
+        assert_eq!(solve(1, &[2], input).unwrap(), vec!("241861950"));
+        assert!(solve(1, &[3], input).is_err());
+        assert!(solve(20, &[1], input).is_err());
+    }
+
+    #[test]
+    fn test_report_day() {
+        let results = report_day(1, &[2], Some("../advent_2020_day_1/input.txt")).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].answer.as_deref(), Some("263819430"));
+        assert_eq!(results[0].answer_type, "u32");
+    }
+
+    #[test]
+    fn test_solve_day_7_target_bag() {
+        let input = "shiny gold bags contain 2 dark red bags.\n\
+            dark red bags contain 2 dark orange bags.\n\
+            dark orange bags contain no other bags.\n\
+            bright white bags contain 1 shiny gold bag.";
+        assert_eq!(solve(7, &[1, 2], input).unwrap(), vec!("1", "6"));
+    }
+}
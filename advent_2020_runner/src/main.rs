@@ -0,0 +1,66 @@
+use advent_2020_common::{fetch::InputProvider, Error};
+use advent_2020_runner::{bench_day, fetch_day, parse_args, report_day, run_day, verify_day, Command, CACHE_DIR};
+
+fn main() -> Result<(), Error> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    match parse_args(&args)? {
+        Command::Run(run_args) if run_args.json => {
+            let mut failures = 0;
+            for day in run_args.days.iter() {
+                for result in report_day(*day, &run_args.parts, run_args.input.as_deref())?.iter() {
+                    println!("{}", result.to_json());
+                    if result.failed() {
+                        failures += 1;
+                    }
+                }
+            }
+            if failures > 0 {
+                return Err(Error::no_solution(format!("{} part(s) failed", failures)));
+            }
+        },
+        Command::Run(run_args) => {
+            for day in run_args.days.iter() {
+                let answers = run_day(*day, &run_args.parts, run_args.input.as_deref())?;
+                for (part, answer) in run_args.parts.iter().zip(answers.iter()) {
+                    println!("Day {} part {}: {}", day, part, answer);
+                }
+            }
+        },
+        Command::Bench(bench_args) => {
+            for day in bench_args.days.iter() {
+                let report = bench_day(*day, bench_args.iterations, bench_args.input.as_deref())?;
+                if bench_args.json {
+                    println!("{}", report.to_json());
+                } else {
+                    println!("{}", report);
+                }
+            }
+        },
+        Command::Verify(days) => {
+            let mut failures = 0;
+            for day in days.iter() {
+                for result in verify_day(*day)?.iter() {
+                    println!("{}", result);
+                    if result.failed() {
+                        failures += 1;
+                    }
+                }
+            }
+            if failures > 0 {
+                return Err(Error::no_solution(format!("{} part(s) failed verification", failures)));
+            }
+        },
+        Command::Fetch(days) => {
+            let provider = InputProvider::from_env(CACHE_DIR);
+            for day in days.iter() {
+                if fetch_day(&provider, *day)? {
+                    println!("Day {}: fetched input", day);
+                } else {
+                    println!("Day {}: input already present", day);
+                }
+            }
+        },
+    }
+    Ok(())
+}